@@ -0,0 +1,385 @@
+use crate::board::Status;
+use crate::{ParseError, Solution};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+//Grey levels of the cells in PNG and PGM images
+const MARKED: u8 = 0;
+const EMPTY: u8 = 255;
+const UNKNOWN: u8 = 128;
+
+fn grey(state: Status) -> u8 {
+    match state {
+        Status::Marked => MARKED,
+        Status::Empty => EMPTY,
+        Status::Unknown => UNKNOWN,
+    }
+}
+
+//Largest image read or written, 4096 x 4096 pixels or the same area in any shape, far beyond
+//any puzzle
+const MAX_PIXELS: usize = 1 << 24;
+
+//The width and height in pixels of the image of a solution, each cell drawn as a scale x scale
+//block, or an error if the image would be larger than MAX_PIXELS
+fn image_size(solution: &Solution, scale: usize) -> io::Result<(usize, usize)> {
+    let width = solution.width().checked_mul(scale);
+    let height = solution.height().checked_mul(scale);
+    match (width, height) {
+        (Some(width), Some(height))
+            if width.checked_mul(height).is_some_and(|n| n <= MAX_PIXELS) =>
+        {
+            Ok((width, height))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "a {}x{} image at scale {} has more than {} pixels",
+                solution.width(),
+                solution.height(),
+                scale,
+                MAX_PIXELS
+            ),
+        )),
+    }
+}
+
+//The grey level of every pixel, row by row, each cell drawn as a scale x scale block
+fn pixels(solution: &Solution, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let mut pixels = Vec::with_capacity(solution.width() * solution.height() * scale * scale);
+    for row in solution.rows() {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|&state| std::iter::repeat_n(grey(state), scale))
+            .collect();
+        for _ in 0..scale {
+            pixels.extend_from_slice(&line);
+        }
+    }
+    pixels
+}
+
+///Writes a solution as an 8-bit greyscale PNG image, each cell a block of scale x scale
+///pixels: black for marked cells, white for empty ones and grey for unknown ones
+pub fn write_png<W: Write>(solution: &Solution, scale: usize, out: W) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (width, height) = image_size(solution, scale)?;
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&pixels(solution, scale))
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+///Writes a solution as a plain (text) PBM image, each cell a block of scale x scale pixels, 1
+///for marked cells and 0 for the others. Being text, two images can be compared with diff.
+pub fn write_pbm<W: Write>(solution: &Solution, scale: usize, mut out: W) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (width, height) = image_size(solution, scale)?;
+    writeln!(out, "P1\n{} {}", width, height)?;
+    for line in pixels(solution, scale).chunks(width.max(1)) {
+        let bits: Vec<&str> = line
+            .iter()
+            .map(|&p| if p == MARKED { "1" } else { "0" })
+            .collect();
+        writeln!(out, "{}", bits.join(" "))?;
+    }
+    Ok(())
+}
+
+///Writes a solution as a plain (text) PGM image with the grey levels of [`write_png`], which
+///unlike PBM shows the unknown cells of a partial solution
+pub fn write_pgm<W: Write>(solution: &Solution, scale: usize, mut out: W) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (width, height) = image_size(solution, scale)?;
+    writeln!(out, "P2\n{} {}\n255", width, height)?;
+    for line in pixels(solution, scale).chunks(width.max(1)) {
+        let levels: Vec<String> = line.iter().map(|p| p.to_string()).collect();
+        writeln!(out, "{}", levels.join(" "))?;
+    }
+    Ok(())
+}
+
+///Reads a picture from a PNG or Netpbm (PBM, PGM or PPM) image file, see [`parse_bitmap`]
+pub fn read_bitmap<P: AsRef<Path>>(file: P, threshold: u8) -> Result<Vec<Vec<bool>>, ParseError> {
+    parse_bitmap(&fs::read(file)?, threshold)
+}
+
+///Parses a PNG or Netpbm image into rows of pixels top to bottom, true for the pixels darker
+///than threshold (1 to 255, 128 splitting the grey levels in half). Colors are turned into
+///grey levels, and transparent pixels count as white. Both the plain (text) and the raw
+///(binary) Netpbm formats are read.
+pub fn parse_bitmap(bytes: &[u8], threshold: u8) -> Result<Vec<Vec<bool>>, ParseError> {
+    let (width, levels) = if bytes.starts_with(&[0x89, b'P', b'N', b'G']) {
+        decode_png(bytes)?
+    } else {
+        decode_netpbm(bytes)?
+    };
+    if levels.is_empty() {
+        return Err(image_error("the image has no pixels"));
+    }
+    Ok(levels
+        .chunks(width)
+        .map(|row| row.iter().map(|&level| level < threshold).collect())
+        .collect())
+}
+
+fn image_error<E: ToString>(e: E) -> ParseError {
+    ParseError::Image(e.to_string())
+}
+
+//Grey level of a color
+fn luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8
+}
+
+//Grey level of a pixel drawn over a white background
+fn over_white(level: u8, alpha: u8) -> u8 {
+    ((level as u32 * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8
+}
+
+//The width of a PNG image and the grey level of each pixel
+fn decode_png(bytes: &[u8]) -> Result<(usize, Vec<u8>), ParseError> {
+    let mut decoder = png::Decoder::new(bytes);
+    //8 bits per sample, palettes and transparent colors turned into RGB and alpha
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    //up to 4 samples per pixel
+    decoder.set_limits(png::Limits {
+        bytes: 4 * MAX_PIXELS,
+    });
+    let mut reader = decoder.read_info().map_err(image_error)?;
+    let (width, height) = reader.info().size();
+    if (width as usize).saturating_mul(height as usize) > MAX_PIXELS {
+        return Err(image_error("the image is too large"));
+    }
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(image_error)?;
+    let levels = buffer[..info.buffer_size()]
+        .chunks(info.color_type.samples())
+        .map(|pixel| match *pixel {
+            [grey, alpha] => over_white(grey, alpha),
+            [r, g, b] => luma(r, g, b),
+            [r, g, b, alpha] => over_white(luma(r, g, b), alpha),
+            _ => pixel[0],
+        })
+        .collect();
+    Ok((info.width as usize, levels))
+}
+
+//Skips whitespace and comments, which run from '#' to the end of the line
+fn skip_blanks(bytes: &[u8], pos: &mut usize) {
+    while let Some(&byte) = bytes.get(*pos) {
+        if byte == b'#' {
+            while bytes.get(*pos).is_some_and(|&b| b != b'\n') {
+                *pos += 1;
+            }
+        } else if byte.is_ascii_whitespace() {
+            *pos += 1;
+        } else {
+            break;
+        }
+    }
+}
+
+//Reads a decimal number of a Netpbm header or plain image
+fn number(bytes: &[u8], pos: &mut usize) -> Result<usize, ParseError> {
+    skip_blanks(bytes, pos);
+    let start = *pos;
+    let mut value: usize = 0;
+    while let Some(digit) = bytes.get(*pos).filter(|b| b.is_ascii_digit()) {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((digit - b'0') as usize))
+            .ok_or_else(|| image_error("number too large in the Netpbm image"))?;
+        *pos += 1;
+    }
+    if *pos == start {
+        return Err(if *pos < bytes.len() {
+            image_error("bad number in the Netpbm image")
+        } else {
+            image_error("the image data is cut short")
+        });
+    }
+    Ok(value)
+}
+
+//The width of a Netpbm image and the grey level of each pixel
+fn decode_netpbm(bytes: &[u8]) -> Result<(usize, Vec<u8>), ParseError> {
+    let kind = match bytes {
+        [b'P', kind @ b'1'..=b'6', ..] => kind - b'0',
+        _ => return Err(image_error("not a PNG, PBM, PGM or PPM image")),
+    };
+    let mut pos = 2;
+    let width = number(bytes, &mut pos)?;
+    let height = number(bytes, &mut pos)?;
+    let bitmap = kind == 1 || kind == 4;
+    let maxval = if bitmap { 1 } else { number(bytes, &mut pos)? };
+    if maxval == 0 || maxval > 65535 {
+        return Err(image_error(
+            "the maximum value of the Netpbm image is out of range",
+        ));
+    }
+    let channels = if kind == 3 || kind == 6 { 3 } else { 1 };
+    let pixels = width
+        .checked_mul(height)
+        .filter(|&pixels| pixels <= MAX_PIXELS)
+        .ok_or_else(|| image_error("the image is too large"))?;
+    let count = pixels * channels;
+    //the raw formats have a single whitespace character before the pixel data
+    let data = bytes.get(pos + 1..).unwrap_or_default();
+    let cut_short = || image_error("the image data is cut short");
+
+    //the samples scaled to 0..=255, 0 being black as in the grey and color formats. The plain
+    //formats grow the vector as they read, the raw ones only reserve it once the data is
+    //known to be there, so the header alone never makes it large.
+    let mut samples = Vec::new();
+    match kind {
+        1 => {
+            while samples.len() < count {
+                skip_blanks(bytes, &mut pos);
+                match bytes.get(pos) {
+                    Some(b'0') => samples.push(255),
+                    Some(b'1') => samples.push(0),
+                    Some(_) => return Err(image_error("bad pixel in the PBM image")),
+                    None => return Err(cut_short()),
+                }
+                pos += 1;
+            }
+        }
+        2 | 3 => {
+            while samples.len() < count {
+                let value = number(bytes, &mut pos)?.min(maxval);
+                samples.push((value * 255 / maxval) as u8);
+            }
+        }
+        4 => {
+            let row_bytes = width.div_ceil(8);
+            if data.len() < row_bytes * height {
+                return Err(cut_short());
+            }
+            samples.reserve(count);
+            for row in data.chunks(row_bytes.max(1)).take(height) {
+                for j in 0..width {
+                    let bit = row[j / 8] >> (7 - j % 8) & 1;
+                    samples.push(if bit == 1 { 0 } else { 255 });
+                }
+            }
+        }
+        _ => {
+            let size = if maxval < 256 { 1 } else { 2 };
+            if data.len() < count * size {
+                return Err(cut_short());
+            }
+            samples.reserve(count);
+            for sample in data.chunks(size).take(count) {
+                let value = sample
+                    .iter()
+                    .fold(0, |v, &b| v << 8 | b as usize)
+                    .min(maxval);
+                samples.push((value * 255 / maxval) as u8);
+            }
+        }
+    }
+    let levels = if channels == 3 {
+        samples.chunks(3).map(|c| luma(c[0], c[1], c[2])).collect()
+    } else {
+        samples
+    };
+    Ok((width, levels))
+}
+
+#[cfg(test)]
+fn corner() -> Solution {
+    // ##
+    // #.
+    crate::Puzzle::new(vec![vec![2], vec![1]], vec![vec![2], vec![1]])
+        .solve()
+        .unwrap()
+}
+
+#[test]
+fn write_netpbm_images() {
+    let mut pbm = Vec::new();
+    write_pbm(&corner(), 1, &mut pbm).unwrap();
+    assert_eq!(String::from_utf8(pbm).unwrap(), "P1\n2 2\n1 1\n1 0\n");
+    let mut pgm = Vec::new();
+    write_pgm(&corner(), 2, &mut pgm).unwrap();
+    assert_eq!(
+        String::from_utf8(pgm).unwrap(),
+        "P2\n4 4\n255\n0 0 0 0\n0 0 0 0\n0 0 255 255\n0 0 255 255\n"
+    );
+}
+
+#[test]
+fn write_png_image() {
+    let mut png = Vec::new();
+    write_png(&corner(), 3, &mut png).unwrap();
+    let decoder = png::Decoder::new(&png[..]);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!((info.width, info.height), (6, 6));
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+    assert_eq!(&buffer[..6], &[0, 0, 0, 0, 0, 0]);
+    assert_eq!(&buffer[30..36], &[0, 0, 0, 255, 255, 255]);
+}
+
+#[test]
+fn refuse_huge_images() {
+    for scale in [5_000_000_000, 100_000, 2049] {
+        let error = write_png(&corner(), scale, Vec::new()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(write_pbm(&corner(), scale, Vec::new()).is_err());
+        assert!(write_pgm(&corner(), scale, Vec::new()).is_err());
+    }
+}
+
+#[test]
+fn read_images() {
+    let picture = vec![vec![true, true], vec![true, false]];
+    assert_eq!(
+        parse_bitmap(b"P1\n# a comment\n2 2\n1 1\n10\n", 128).unwrap(),
+        picture
+    );
+    assert_eq!(parse_bitmap(b"P4 2 2\n\xc0\x80", 128).unwrap(), picture);
+    assert_eq!(
+        parse_bitmap(b"P2 2 2 15 0 3 7 15", 128).unwrap(),
+        vec![vec![true, true], vec![true, false]]
+    );
+    assert_eq!(
+        parse_bitmap(b"P6 1 1 255 \x00\x00\xff", 128).unwrap(),
+        vec![vec![true]]
+    );
+    let mut png = Vec::new();
+    write_png(&corner(), 2, &mut png).unwrap();
+    let read = parse_bitmap(&png, 128).unwrap();
+    assert_eq!(read.len(), 4);
+    assert_eq!(read[3], vec![true, true, false, false]);
+
+    assert!(parse_bitmap(b"P1 2 2 1 1 1", 128).is_err());
+    assert!(parse_bitmap(b"P5 2 2 255 \x00", 128).is_err());
+    assert!(parse_bitmap(b"P1 0 0", 128).is_err());
+    assert!(parse_bitmap(b"GIF89a", 128).is_err());
+    assert!(parse_bitmap(&png[..20], 128).is_err());
+
+    //headers claiming huge images fail before anything is allocated for them
+    let too_large = |bytes: &[u8]| parse_bitmap(bytes, 128).unwrap_err().to_string();
+    assert_eq!(too_large(b"P1 100000 100000\n1"), "the image is too large");
+    assert_eq!(too_large(b"P1 4000 4000\n1"), "the image data is cut short");
+    assert_eq!(
+        too_large(b"P5 4000 4000 255\n\x00"),
+        "the image data is cut short"
+    );
+    let mut huge = Vec::new();
+    let mut encoder = png::Encoder::new(&mut huge, 100_000, 100_000);
+    encoder.set_color(png::ColorType::Grayscale);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_chunk(png::chunk::IDAT, &[]).unwrap();
+    drop(writer);
+    assert_eq!(too_large(&huge), "the image is too large");
+}
@@ -0,0 +1,1140 @@
+use crate::grid::Grid;
+use crate::propagate::WorkQueue;
+use crate::solver::SolveStats;
+use crate::trace::{Rule, SolveTrace};
+use serde::{Deserialize, Serialize};
+use std::cmp;
+
+///State of a single cell in the grid
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Empty,
+    Marked,
+    #[default]
+    Unknown,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct NonoKey {
+    pub(crate) value: usize,
+    pub(crate) upper_bound: usize,
+    pub(crate) lower_bound: usize,
+}
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Board {
+    pub(crate) grid: Grid,
+    pub(crate) h_keys: Vec<Vec<NonoKey>>,
+    pub(crate) v_keys: Vec<Vec<NonoKey>>,
+    pub(crate) size: Vec<usize>,
+}
+
+impl Board {
+    ///Builds an unsolved board from the row (vertical) and column (horizontal) clues.
+    ///An empty clue line is stored as a single 0 key, matching the puzzle file format.
+    pub(crate) fn new(row_clues: &[Vec<usize>], col_clues: &[Vec<usize>]) -> Board {
+        let to_keys = |clues: &[Vec<usize>]| -> Vec<Vec<NonoKey>> {
+            clues
+                .iter()
+                .map(|line| {
+                    if line.is_empty() {
+                        vec![NonoKey::default()]
+                    } else {
+                        line.iter()
+                            .map(|&value| NonoKey {
+                                value,
+                                ..Default::default()
+                            })
+                            .collect()
+                    }
+                })
+                .collect()
+        };
+        let height = row_clues.len();
+        let width = col_clues.len();
+        Board {
+            grid: Grid::new(height, width),
+            v_keys: to_keys(row_clues),
+            h_keys: to_keys(col_clues),
+            size: vec![height, width],
+        }
+    }
+
+    //A copy of the board, leaving out the undo trail of the grid
+    pub(crate) fn snapshot(&self) -> Board {
+        Board {
+            grid: self.grid.snapshot(),
+            h_keys: self.h_keys.clone(),
+            v_keys: self.v_keys.clone(),
+            size: self.size.clone(),
+        }
+    }
+
+    ///Returns true if all the cells in the grid are set to either marked or empty
+    pub(crate) fn complete_grid(&self) -> bool {
+        self.grid.is_complete()
+    }
+
+    //returns the maximum number of keys in a set
+    pub(crate) fn get_key_dimensions(&self, vkey: &mut usize, hkey: &mut usize) {
+        for i in self.v_keys.iter() {
+            if i.len() > *vkey {
+                *vkey = i.len();
+            }
+        }
+        for i in self.h_keys.iter() {
+            if i.len() > *hkey {
+                *hkey = i.len();
+            }
+        }
+    }
+
+    //loops until no further deductive steps are possible to make progress on the solution
+    //once the rules stall, every line is solved exactly to find the cells they missed
+    //returns false if a row/column contradicts its keys
+    pub(crate) fn solve(&mut self, stats: &mut SolveStats, trace: &mut SolveTrace) -> bool {
+        self.determine_bounds_v();
+        self.determine_bounds_h();
+        let mut progress = true;
+        while progress {
+            stats.rounds += 1;
+            let fixed = &mut stats.fixed;
+            progress = self.apply_rule(
+                [
+                    Board::definite_within_bounds_v,
+                    Board::definite_within_bounds_h,
+                ],
+                &mut fixed.definite_within_bounds,
+                Rule::DefiniteWithinBounds,
+                trace,
+            ) | self.apply_rule(
+                [
+                    Board::determine_spaces_between_keys_v,
+                    Board::determine_spaces_between_keys_h,
+                ],
+                &mut fixed.spaces_between_keys,
+                Rule::SpacesBetweenKeys,
+                trace,
+            ) | self.apply_rule(
+                [Board::separate_keys_v, Board::separate_keys_h],
+                &mut fixed.separate_keys,
+                Rule::SeparateKeys,
+                trace,
+            ) | self.apply_rule(
+                [Board::tighten_bounds_v, Board::tighten_bounds_h],
+                &mut fixed.tighten_bounds,
+                Rule::TightenBounds,
+                trace,
+            ) | self.apply_rule(
+                [Board::complete_groups_v, Board::complete_groups_h],
+                &mut fixed.complete_groups,
+                Rule::CompleteGroups,
+                trace,
+            );
+        }
+        let mut queue = WorkQueue::all(self.size[0], self.size[1]);
+        match self.propagate(&mut queue, stats, trace) {
+            Ok(()) => true,
+            Err(line) => {
+                trace.contradiction(line);
+                false
+            }
+        }
+    }
+
+    //runs the row and column versions of a rule, adding the number of cells they set to fixed
+    fn apply_rule(
+        &mut self,
+        rule: [fn(&mut Board) -> bool; 2],
+        fixed: &mut usize,
+        kind: Rule,
+        trace: &mut SolveTrace,
+    ) -> bool {
+        let unknown = self.unknown_count();
+        let mut progress = false;
+        for (n, rule) in rule.iter().enumerate() {
+            let before = if trace.is_recording() {
+                Some(self.grid.snapshot())
+            } else {
+                None
+            };
+            progress |= rule(self);
+            if let Some(before) = before {
+                trace.rule_applied(kind, n == 0, &before, &self.grid);
+            }
+        }
+        *fixed += unknown.saturating_sub(self.unknown_count());
+        progress
+    }
+
+    //figure out the bounds for each key by counting from the edgdes, considering preceding keys
+    fn determine_bounds_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let mut s = 0;
+            let len = self.v_keys[i].len();
+            for j in 0..len {
+                self.v_keys[i][j].upper_bound = s;
+                s += self.v_keys[i][j].value + 1; //number of spaces taken up by previous key value + space
+                progress = true;
+            }
+            s = 0;
+            for j in (0..len).rev() {
+                if width - s >= self.v_keys[i][j].value {
+                    self.v_keys[i][j].lower_bound = width - s;
+                    s += self.v_keys[i][j].value + 1;
+                    progress = true;
+                }
+            }
+        }
+        progress
+    }
+
+    fn determine_bounds_h(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let mut s = 0;
+            let len = self.h_keys[i].len();
+            for j in 0..len {
+                //from front to back
+                self.h_keys[i][j].upper_bound = s;
+                s += self.h_keys[i][j].value + 1; //number of spaces taken up by previous key value + space
+                progress = true;
+            }
+            s = 0;
+            for j in (0..len).rev() {
+                if height - s >= self.h_keys[i][j].value {
+                    self.h_keys[i][j].lower_bound = height - s;
+                    s += self.h_keys[i][j].value + 1;
+                    progress = true;
+                }
+            }
+        }
+        progress
+    }
+
+    //Set cells to marked if a given bound has definite marked cells within it.
+    //If the bound indicates that the entire key has been marked, mark the bounds as empty.
+    fn definite_within_bounds_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let len = self.v_keys[i].len();
+            for j in 0..len {
+                let def_upper = self.v_keys[i][j].lower_bound - self.v_keys[i][j].value;
+                let def_lower = self.v_keys[i][j].upper_bound + self.v_keys[i][j].value;
+
+                if def_lower >= def_upper {
+                    for n in def_upper..def_lower {
+                        if n >= width {
+                            continue;
+                        }
+                        if matches!(self.grid.get(i, n), Status::Unknown) {
+                            self.grid.set(i, n, Status::Marked);
+                            progress = true;
+                        }
+                    }
+                    if def_lower - def_upper == self.v_keys[i][j].value {
+                        if def_lower < width - 1
+                            && matches!(self.grid.get(i, def_lower), Status::Unknown)
+                        {
+                            self.grid.set(i, def_lower, Status::Empty);
+                            progress = true;
+                        }
+                        if def_upper > 0
+                            && matches!(self.grid.get(i, def_upper - 1), Status::Unknown)
+                        {
+                            self.grid.set(i, def_upper - 1, Status::Empty);
+                            progress = true;
+                        }
+                    }
+                }
+            }
+        }
+        progress
+    }
+    fn definite_within_bounds_h(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let len = self.h_keys[i].len();
+            for j in 0..len {
+                if self.h_keys[i][j].lower_bound < self.h_keys[i][j].value {
+                    continue;
+                }
+                let def_upper = self.h_keys[i][j].lower_bound - self.h_keys[i][j].value;
+                let def_lower = self.h_keys[i][j].upper_bound + self.h_keys[i][j].value;
+                if def_lower > def_upper {
+                    for n in def_upper..def_lower {
+                        if n >= height {
+                            continue;
+                        }
+                        if matches!(self.grid.get(n, i), Status::Unknown) {
+                            self.grid.set(n, i, Status::Marked);
+                            progress = true;
+                        }
+                    }
+                    if def_lower - def_upper == self.h_keys[i][j].value {
+                        if def_lower < height - 1
+                            && matches!(self.grid.get(def_lower, i), Status::Unknown)
+                        {
+                            self.grid.set(def_lower, i, Status::Empty);
+                            progress = true;
+                        }
+                        if def_upper > 0
+                            && matches!(self.grid.get(def_upper - 1, i), Status::Unknown)
+                        {
+                            self.grid.set(def_upper - 1, i, Status::Empty);
+                            progress = true;
+                        }
+                    }
+                }
+            }
+        }
+        progress
+    }
+
+    //Zone key bounds further according to known empty cells
+    fn separate_keys_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let len = self.v_keys[i].len();
+            for j in 0..len {
+                let key = &mut self.v_keys[i][j];
+                for n in key.upper_bound..key.lower_bound {
+                    match self.grid.get(i, n) {
+                        Status::Empty => {
+                            if n > key.lower_bound {
+                                continue;
+                            }
+                            let upper_space = n - key.upper_bound;
+                            let lower_space = key.lower_bound - 1 - n;
+
+                            if upper_space < key.value {
+                                key.upper_bound = n + 1;
+                                progress = true;
+                            }
+                            if lower_space < key.value && n >= key.value {
+                                key.lower_bound = n;
+                                progress = true;
+                            }
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+        progress
+    }
+    fn separate_keys_h(&mut self) -> bool {
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let len = self.h_keys[i].len();
+            for j in 0..len {
+                let key = &mut self.h_keys[i][j];
+                for n in key.upper_bound..key.lower_bound {
+                    match self.grid.get(n, i) {
+                        Status::Empty => {
+                            if n > key.lower_bound {
+                                continue;
+                            }
+                            let upper_space = n - key.upper_bound;
+                            let lower_space = key.lower_bound - n - 1;
+
+                            if upper_space < key.value {
+                                key.upper_bound = n + 1;
+                                progress = true;
+                            }
+                            if lower_space < key.value && n >= key.value {
+                                key.lower_bound = n;
+                                progress = true;
+                            }
+                        }
+                        _ => continue,
+                    }
+                }
+            }
+        }
+        progress
+    }
+
+    //Marks spaces between keys in a key set if their bounds don't intersect
+    fn determine_spaces_between_keys_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let len = self.v_keys[i].len();
+            let mut prev_lower_bound = self.v_keys[i][0].lower_bound;
+            for j in 1..len {
+                if self.v_keys[i][j].upper_bound > prev_lower_bound {
+                    for n in prev_lower_bound..self.v_keys[i][j].upper_bound {
+                        if matches!(self.grid.get(i, n), Status::Unknown) {
+                            self.grid.set(i, n, Status::Empty);
+                            progress = true;
+                        }
+                    }
+                }
+                prev_lower_bound = self.v_keys[i][j].lower_bound;
+            }
+        }
+        progress
+    }
+    fn determine_spaces_between_keys_h(&mut self) -> bool {
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let len = self.h_keys[i].len();
+            let mut prev_lower_bound = self.h_keys[i][0].lower_bound;
+            for j in 1..len {
+                if self.h_keys[i][j].upper_bound > prev_lower_bound {
+                    for n in prev_lower_bound..self.h_keys[i][j].upper_bound {
+                        if matches!(self.grid.get(n, i), Status::Unknown) {
+                            self.grid.set(n, i, Status::Empty);
+                            progress = true;
+                        }
+                    }
+                }
+                prev_lower_bound = self.h_keys[i][j].lower_bound;
+            }
+        }
+        progress
+    }
+
+    //Further reduce key bounds if it contains a marked cell that is exclusive to the given bound
+    fn tighten_bounds_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let len = self.v_keys[i].len();
+            let mut prev_bound;
+            let mut next_bound;
+            for j in 0..len {
+                if j < len - 1 {
+                    next_bound = self.v_keys[i][j + 1].upper_bound;
+                } else {
+                    next_bound = self.v_keys[i][j].lower_bound;
+                }
+
+                if j > 0 {
+                    prev_bound = self.v_keys[i][j - 1].lower_bound;
+                } else {
+                    prev_bound = self.v_keys[i][j].upper_bound;
+                }
+
+                let key = &mut self.v_keys[i][j];
+                let min_def = cmp::min(key.lower_bound, prev_bound);
+                let max_def = cmp::max(key.upper_bound, next_bound);
+                if min_def >= max_def {
+                    continue;
+                }
+                if min_def < max_def {
+                    for n in min_def..max_def {
+                        if matches!(self.grid.get(i, n), Status::Marked) {
+                            let mut upper = 0;
+                            if n >= key.value {
+                                upper = n - key.value;
+                            }
+                            let lower = n + key.value;
+                            if key.upper_bound < upper {
+                                key.upper_bound = upper;
+                                progress = true;
+                            }
+                            if key.lower_bound > lower && lower >= key.value {
+                                key.lower_bound = lower;
+                                progress = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        progress
+    }
+    fn tighten_bounds_h(&mut self) -> bool {
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let len = self.h_keys[i].len();
+            let mut prev_bound;
+            let mut next_bound;
+            for j in 0..len {
+                if j < len - 1 {
+                    next_bound = self.h_keys[i][j + 1].upper_bound;
+                } else {
+                    next_bound = self.h_keys[i][j].lower_bound;
+                }
+
+                if j > 0 {
+                    prev_bound = self.h_keys[i][j - 1].lower_bound;
+                } else {
+                    prev_bound = self.h_keys[i][j].upper_bound;
+                }
+
+                let key = &mut self.h_keys[i][j];
+                let min_def = cmp::min(key.lower_bound, prev_bound);
+                let max_def = cmp::max(key.upper_bound, next_bound);
+                if min_def >= max_def {
+                    continue;
+                }
+
+                if max_def > min_def {
+                    for n in min_def..max_def {
+                        if matches!(self.grid.get(n, i), Status::Marked) {
+                            let mut upper = 0;
+                            if n >= key.value {
+                                upper = n - key.value;
+                            }
+                            let lower = n + key.value;
+                            if key.upper_bound < upper {
+                                key.upper_bound = upper;
+                                progress = true;
+                            }
+                            if key.lower_bound > lower && lower >= key.value {
+                                key.lower_bound = lower;
+                                progress = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        progress
+    }
+
+    //Mark the remaining cells in the row/cell if all the keys in the set have been determined
+    fn complete_groups_v(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..height {
+            let len = self.v_keys[i].len();
+            let mut complete = 0;
+            let mut cell_state = 0;
+            for j in 0..width {
+                match self.grid.get(i, j) {
+                    Status::Marked => {
+                        if cell_state == 0 {
+                            cell_state = 1;
+                        }
+                    }
+                    Status::Empty => {
+                        if cell_state == 1 {
+                            complete += 1;
+                        }
+                        cell_state = 0;
+                    }
+                    Status::Unknown => cell_state = 2,
+                }
+            }
+            if complete == len {
+                for n in 0..width {
+                    if matches!(self.grid.get(i, n), Status::Unknown) {
+                        progress = true;
+                        self.grid.set(i, n, Status::Empty);
+                    }
+                }
+            }
+        }
+        progress
+    }
+    fn complete_groups_h(&mut self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut progress = false;
+        for i in 0..width {
+            let mut complete = 0;
+            let mut cell_state = 0;
+            for j in 0..height {
+                match self.grid.get(j, i) {
+                    Status::Marked => {
+                        if cell_state == 0 {
+                            cell_state = 1;
+                        }
+                    }
+                    Status::Empty => {
+                        if cell_state == 1 {
+                            complete += 1;
+                        }
+                        cell_state = 0;
+                    }
+                    Status::Unknown => cell_state = 2,
+                }
+            }
+            if complete == self.h_keys[i].len() {
+                for n in 0..height {
+                    if matches!(self.grid.get(n, i), Status::Unknown) {
+                        progress = true;
+                        self.grid.set(n, i, Status::Empty);
+                    }
+                }
+            }
+        }
+        progress
+    }
+
+    //Return true if the pattern in keys matches the pattern in compare_to
+    fn compare_keys_start(&self, keys: &[usize], compare_to: &[NonoKey]) -> bool {
+        let len = keys.len();
+        if keys.len() > compare_to.len() {
+            return false;
+        }
+
+        for i in 0..len {
+            if keys[i] != compare_to[i].value {
+                return false;
+            }
+        }
+        true
+    }
+    //Return true if both vectors match each other entirely
+    fn compare_keys_whole(&self, keys: &[usize], compare_to: &[NonoKey]) -> bool {
+        if keys.len() != compare_to.len() {
+            return false;
+        }
+        let len = keys.len();
+        for i in 0..len {
+            if keys[i] != compare_to[i].value {
+                return false;
+            }
+        }
+        true
+    }
+
+    //Return false if a row/column consists of cells that don't correspond with the appropriate key set
+    pub(crate) fn valid_grid(&self) -> bool {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        let mut keys = Vec::with_capacity(10);
+        for i in 0..height {
+            keys.clear();
+            let mut group = 0;
+            let mut t = false;
+            for j in 0..width {
+                match self.grid.get(i, j) {
+                    Status::Marked => {
+                        group += 1;
+                    }
+                    Status::Empty => {
+                        if group > 0 {
+                            keys.push(group);
+                        }
+                        group = 0;
+                    }
+                    Status::Unknown => {
+                        t = true;
+                        break;
+                    }
+                }
+            }
+
+            if group > 0 && !t {
+                keys.push(group);
+            }
+            if t {
+                if !self.compare_keys_start(&keys, &self.v_keys[i]) {
+                    return false;
+                }
+            } else {
+                if keys.is_empty() {
+                    keys.push(0);
+                }
+                if !self.compare_keys_whole(&keys, &self.v_keys[i]) {
+                    return false;
+                }
+            }
+        }
+        for i in 0..width {
+            keys.clear();
+            let mut group = 0;
+            let mut t = false;
+            for j in 0..height {
+                match self.grid.get(j, i) {
+                    Status::Marked => group += 1,
+                    Status::Empty => {
+                        if group > 0 {
+                            keys.push(group);
+                        }
+                        group = 0;
+                    }
+                    Status::Unknown => {
+                        t = true;
+                        break;
+                    }
+                }
+            }
+            if group > 0 && !t {
+                keys.push(group);
+            }
+            if t {
+                if !self.compare_keys_start(&keys, &self.h_keys[i]) {
+                    return false;
+                }
+            } else {
+                if keys.is_empty() {
+                    keys.push(0);
+                }
+                if !self.compare_keys_whole(&keys, &self.h_keys[i]) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+    //Finds and returns the first unmarked cell in the grid, None if every cell is known
+    pub(crate) fn get_unknown_cell(&self) -> Option<(usize, usize)> {
+        let height = self.v_keys.len();
+        let width = self.h_keys.len();
+        for i in 0..height {
+            for j in 0..width {
+                if matches!(self.grid.get(i, j), Status::Unknown) {
+                    return Some((i, j));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[test]
+fn complete_grid() {
+    let mut b = Board {
+        grid: Grid::filled(10, 10, Status::Marked),
+        ..Default::default()
+    };
+    b.size = vec![10, 10];
+    let result = b.complete_grid();
+    assert!(result);
+}
+#[test]
+fn incomplete_grid() {
+    let mut b = Board {
+        grid: Grid::filled(10, 10, Status::Marked),
+        ..Default::default()
+    };
+    b.grid.set(0, 9, Status::Unknown);
+    b.size = vec![10, 10];
+    let result = b.complete_grid();
+    assert!(!result);
+}
+#[test]
+fn max_2d_vector() {
+    let mut b: Board = Default::default();
+    let mut v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 1,
+            upper_bound: 0,
+            lower_bound: 0
+        };
+        1
+    ];
+    b.h_keys.push(v);
+    v = vec![
+        NonoKey {
+            value: 1,
+            upper_bound: 0,
+            lower_bound: 0
+        };
+        5
+    ];
+    b.h_keys.push(v);
+
+    v = vec![
+        NonoKey {
+            value: 1,
+            upper_bound: 0,
+            lower_bound: 0
+        };
+        3
+    ];
+    b.v_keys.push(v);
+    v = vec![
+        NonoKey {
+            value: 1,
+            upper_bound: 0,
+            lower_bound: 0
+        };
+        7
+    ];
+    b.v_keys.push(v);
+    v = vec![
+        NonoKey {
+            value: 1,
+            upper_bound: 0,
+            lower_bound: 0
+        };
+        1
+    ];
+    b.v_keys.push(v);
+
+    let mut hmax = 0;
+    let mut vmax = 0;
+
+    b.get_key_dimensions(&mut vmax, &mut hmax);
+    assert_eq!(vmax, 7);
+    assert_eq!(hmax, 5);
+}
+#[test]
+fn valid_bounds() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 4,
+            upper_bound: 0,
+            lower_bound: 0,
+        },
+        NonoKey {
+            value: 7,
+            upper_bound: 0,
+            lower_bound: 0,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 1,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.size = vec![15, 1];
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    assert_eq!(b.v_keys[0][0].upper_bound, 0);
+    assert_eq!(b.v_keys[0][0].lower_bound, 7);
+    assert_eq!(b.v_keys[0][1].upper_bound, 5);
+    assert_eq!(b.v_keys[0][1].lower_bound, 15);
+}
+#[test]
+fn marked_bounds() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 4,
+            upper_bound: 0,
+            lower_bound: 0,
+        },
+        NonoKey {
+            value: 7,
+            upper_bound: 0,
+            lower_bound: 0,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 1,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.size = vec![1, 15];
+    b.grid = Grid::new(1, 15);
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    b.definite_within_bounds_v();
+    b.definite_within_bounds_h();
+    assert_eq!(b.grid.get(0, 3), Status::Marked); //15 - 7 - 1 - 4 overlaps with -1+4
+    assert_eq!(b.grid.get(0, 8), Status::Marked); //the key "7" definitely exists within the bounds -1+4+1+7 through 15 - 7  (8 through 11)
+    assert_eq!(b.grid.get(0, 9), Status::Marked);
+    assert_eq!(b.grid.get(0, 10), Status::Marked);
+    assert_eq!(b.grid.get(0, 11), Status::Marked);
+}
+
+#[test]
+fn marked_and_separated_bounds() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 0,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.h_keys[1][0].value = 1;
+    b.h_keys[2][0].value = 1;
+    b.h_keys[10][0].value = 1;
+    b.h_keys[11][0].value = 1;
+    b.h_keys[12][0].value = 1;
+    b.size = vec![1, 15];
+    b.grid = Grid::new(1, 15);
+    b.grid.set(0, 6, Status::Empty);
+    b.grid.set(0, 1, Status::Marked);
+    b.grid.set(0, 11, Status::Marked);
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    b.definite_within_bounds_v();
+    b.definite_within_bounds_h();
+    b.tighten_bounds_v();
+    b.tighten_bounds_h();
+    b.determine_spaces_between_keys_v();
+    b.determine_spaces_between_keys_h();
+    assert_eq!(b.grid.get(0, 1), Status::Marked);
+    assert_eq!(b.grid.get(0, 2), Status::Marked);
+    assert_eq!(b.grid.get(0, 3), Status::Empty);
+    assert_eq!(b.grid.get(0, 4), Status::Empty);
+    assert_eq!(b.grid.get(0, 5), Status::Empty);
+    assert_eq!(b.grid.get(0, 6), Status::Empty);
+    assert_eq!(b.grid.get(0, 7), Status::Empty);
+    assert_eq!(b.grid.get(0, 8), Status::Empty);
+    assert_eq!(b.grid.get(0, 10), Status::Marked);
+    assert_eq!(b.grid.get(0, 11), Status::Marked);
+    assert_eq!(b.grid.get(0, 12), Status::Marked);
+
+    assert_eq!(b.grid.get(0, 0), Status::Unknown);
+    assert_eq!(b.grid.get(0, 13), Status::Unknown);
+    assert_eq!(b.grid.get(0, 14), Status::Unknown);
+}
+
+#[test]
+fn whole_key_comparison() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+        NonoKey {
+            value: 4,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 5,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+    ];
+    b.v_keys.push(v);
+    let keys = vec![2, 3, 4, 5];
+    assert!(b.compare_keys_whole(&keys, &b.v_keys[0]));
+}
+#[test]
+fn partial_key_comparison() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+        NonoKey {
+            value: 4,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 5,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+    ];
+    b.v_keys.push(v);
+    let keys = vec![2, 3];
+    assert!(b.compare_keys_start(&keys, &b.v_keys[0]));
+}
+
+#[test]
+fn valid_combination() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 0,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.h_keys[1][0].value = 1;
+    b.h_keys[2][0].value = 1;
+    b.h_keys[10][0].value = 1;
+    b.h_keys[11][0].value = 1;
+    b.h_keys[12][0].value = 1;
+    b.size = vec![1, 15];
+    b.grid = Grid::new(1, 15);
+    b.grid.set(0, 6, Status::Empty);
+    b.grid.set(0, 1, Status::Marked);
+    b.grid.set(0, 11, Status::Marked);
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    b.definite_within_bounds_v();
+    b.definite_within_bounds_h();
+    b.tighten_bounds_v();
+    b.tighten_bounds_h();
+    b.determine_spaces_between_keys_v();
+    b.determine_spaces_between_keys_h();
+    assert!(b.valid_grid());
+}
+
+#[test]
+fn not_valid_combination() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 0,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.h_keys[1][0].value = 1;
+    b.h_keys[2][0].value = 1;
+    b.h_keys[10][0].value = 1;
+    b.h_keys[11][0].value = 1;
+    b.h_keys[12][0].value = 1;
+    b.size = vec![1, 15];
+    b.grid = Grid::new(1, 15);
+    b.grid.set(0, 6, Status::Empty);
+    b.grid.set(0, 1, Status::Marked);
+    b.grid.set(0, 11, Status::Marked);
+
+    b.grid.set(0, 14, Status::Marked);
+
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    b.definite_within_bounds_v();
+    b.definite_within_bounds_h();
+    b.tighten_bounds_v();
+    b.tighten_bounds_h();
+    b.determine_spaces_between_keys_v();
+    b.determine_spaces_between_keys_h();
+    assert!(!b.valid_grid());
+}
+
+#[test]
+fn unknown_cell_test() {
+    let mut b: Board = Default::default();
+    let v: Vec<NonoKey> = vec![
+        NonoKey {
+            value: 2,
+            upper_bound: 0,
+            lower_bound: 5,
+        },
+        NonoKey {
+            value: 3,
+            upper_bound: 7,
+            lower_bound: 14,
+        },
+    ];
+    b.v_keys.push(v);
+    let v2 = vec![
+        vec![
+            NonoKey {
+                value: 0,
+                upper_bound: 0,
+                lower_bound: 0
+            };
+            1
+        ];
+        15
+    ];
+    b.h_keys = v2;
+    b.h_keys[1][0].value = 1;
+    b.h_keys[2][0].value = 1;
+    b.h_keys[10][0].value = 1;
+    b.h_keys[11][0].value = 1;
+    b.h_keys[12][0].value = 1;
+    b.size = vec![1, 15];
+    b.grid = Grid::new(1, 15);
+    b.grid.set(0, 6, Status::Empty);
+    b.grid.set(0, 1, Status::Marked);
+    b.grid.set(0, 11, Status::Marked);
+    b.grid.set(0, 0, Status::Empty);
+    b.grid.set(0, 14, Status::Empty);
+    b.determine_bounds_v();
+    b.determine_bounds_h();
+    b.definite_within_bounds_v();
+    b.definite_within_bounds_h();
+    b.tighten_bounds_v();
+    b.tighten_bounds_h();
+    b.determine_spaces_between_keys_v();
+    b.determine_spaces_between_keys_h();
+    let (i, j) = b.get_unknown_cell().unwrap();
+    assert_eq!(b.grid.get(i, j), Status::Unknown);
+    let solved = Board {
+        grid: Grid::filled(2, 2, Status::Marked),
+        ..Default::default()
+    };
+    assert_eq!(solved.get_unknown_cell(), None);
+}
@@ -0,0 +1,614 @@
+use nonogram_solver::{Branching, Difficulty, Engine, Level};
+use std::time::{Duration, Instant};
+
+pub const USAGE: &str = "\
+Usage:
+    nonogram_solver                      choose one of the bundled puzzles interactively
+    nonogram_solver solve [OPTIONS] <FILE>...
+    nonogram_solver compare <FILE>...    count the guesses each branching strategy needs
+    nonogram_solver explain <FILE>       list every deduction and guess made solving a puzzle
+    nonogram_solver rate <FILE>...       grade puzzles by the techniques needed to solve them
+    nonogram_solver image [-o FILE] [-t SECONDS] [--threshold N] [--fix givens|flips] <IMAGE>
+                                         turn a PNG or Netpbm image into a puzzle, written to
+                                         FILE (as JSON or TOML for those extensions) or
+                                         stdout, and check within SECONDS (default 10) that it
+                                         has a single solution; pixels darker than N (1-255,
+                                         default 128) are marked. With --fix, a puzzle with
+                                         several solutions gets the fewest givens (written as
+                                         JSON unless FILE is TOML) or flipped cells of the
+                                         picture that give it one, searched for within the
+                                         same SECONDS, and the edits are listed
+    nonogram_solver generate [OPTIONS] <WIDTH>x<HEIGHT>
+                                         make random puzzles with a single solution
+
+Options:
+    -f, --format <FORMAT>    output format: text (default), terminal or json (one object per
+                             puzzle and line)
+    -o, --output <FILE>      write the solutions to FILE instead of stdout
+    -t, --timeout <SECONDS>  give up on a puzzle after SECONDS and print the cells found so far
+    -e, --engine <ENGINE>    how to solve once deduction stalls: search (default) or sat (only
+                             in builds with the sat feature)
+    -b, --branching <NAME>   how to pick the cell to guess: first, line, probability (default)
+                             or probe
+    -l, --level <LEVEL>      deduction before each guess: line (default) or probe, which tries
+                             both states of every unknown cell
+    -n, --solutions <N>      keep searching for up to N solutions and highlight the cells where
+                             they differ
+    -s, --stats <FORMAT>     report solver counters on stderr: text or json (one object per line)
+    -j, --threads <N>        search with N threads (only in builds with the parallel feature)
+        --svg <FILE>         also draw the solution with its clues as an SVG image, with the
+                             name of the puzzle file added to FILE when solving several
+        --guides             draw a thicker line every 5 cells in the SVG image
+        --blank              draw the blank puzzle sheet in the SVG image instead
+        --png <FILE>         also write the solution as a PNG image, one pixel per cell
+        --pbm <FILE>         also write the solution as a plain text PBM image
+        --pgm <FILE>         also write the solution as a plain text PGM image, with the
+                             unknown cells of a partial solution in grey
+        --scale <N>          draw each cell as a block of N x N pixels in the bitmaps
+    -q, --quiet              print nothing, only report the result through the exit code
+    -h, --help               print this message
+
+Generate options:
+        --density <D>        share of marked cells in the random grids, 0 to 1 (default 0.5)
+        --seed <N>           seed of the first puzzle, the next ones use N+1, N+2... (default:
+                             from the clock, printed to regenerate the puzzles later)
+        --difficulty <NAME>  only keep puzzles rated easy, medium, hard or expert
+        --attempts <N>       random grids to try for each puzzle before giving up (default 100)
+        --count <N>          number of puzzles (default 1)
+    -o, --output <FILE>      write the puzzles to FILE (numbered when there are several, as
+                             JSON or TOML for those extensions) instead of stdout
+
+Exit codes:
+    0  every puzzle was solved
+    1  a puzzle has no solution
+    2  a puzzle file or the command line is invalid
+    3  a puzzle timed out, or generate gave up on a puzzle
+    4  a puzzle has more than one solution (checked by solve with --solutions, and by image)";
+
+//Names of the branching strategies on the command line
+pub const BRANCHINGS: [(&str, Branching); 4] = [
+    ("first", Branching::FirstUnknown),
+    ("line", Branching::MostConstrainedLine),
+    ("probability", Branching::Probability),
+    ("probe", Branching::Probing),
+];
+
+//How the solved grid is written out
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Terminal,
+    //one SolveReport per puzzle, as a line of JSON
+    Json,
+}
+
+//How the solver counters are reported
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StatsFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub files: Vec<String>,
+    pub format: Format,
+    pub output: Option<String>,
+    pub timeout: Option<Duration>,
+    pub quiet: bool,
+    pub engine: Engine,
+    pub branching: Branching,
+    pub level: Level,
+    pub solutions: usize,
+    pub stats: Option<StatsFormat>,
+    pub threads: usize,
+    pub svg: Option<String>,
+    pub guides: bool,
+    pub blank: bool,
+    pub png: Option<String>,
+    pub pbm: Option<String>,
+    pub pgm: Option<String>,
+    pub scale: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ImageOptions {
+    pub file: String,
+    pub output: Option<String>,
+    pub threshold: u8,
+    pub fix: Option<Fix>,
+    //time allowed for the uniqueness check
+    pub timeout: Duration,
+}
+
+//How the image command makes an ambiguous picture uniquely solvable
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fix {
+    Givens,
+    Flips,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateArgs {
+    pub width: usize,
+    pub height: usize,
+    pub density: f64,
+    pub seed: Option<u64>,
+    pub difficulty: Option<Difficulty>,
+    pub attempts: usize,
+    pub count: usize,
+    pub output: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Interactive,
+    Solve(Options),
+    Compare(Vec<String>),
+    Explain(String),
+    Rate(Vec<String>),
+    Image(ImageOptions),
+    Generate(GenerateArgs),
+    Help,
+}
+
+//Result of a single puzzle, ordered from best to worst
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Solved,
+    NotUnique,
+    Unsolvable,
+    Invalid,
+    Timeout,
+}
+
+impl Outcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Solved => 0,
+            Outcome::Unsolvable => 1,
+            Outcome::Invalid => 2,
+            Outcome::Timeout => 3,
+            Outcome::NotUnique => 4,
+        }
+    }
+}
+
+//Parses the command line arguments, not including the program name
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        None => return Ok(Command::Interactive),
+        Some("solve") => {}
+        Some(command @ "compare") | Some(command @ "rate") => {
+            let files: Vec<String> = args.collect();
+            if files.is_empty() {
+                return Err("no puzzle files given".to_string());
+            }
+            return Ok(if command == "compare" {
+                Command::Compare(files)
+            } else {
+                Command::Rate(files)
+            });
+        }
+        Some("explain") => {
+            return match (args.next(), args.next()) {
+                (Some(file), None) => Ok(Command::Explain(file)),
+                (None, _) => Err("no puzzle file given".to_string()),
+                (Some(_), Some(_)) => Err("explain takes a single puzzle file".to_string()),
+            };
+        }
+        Some("image") => return parse_image_args(args),
+        Some("generate") => return parse_generate_args(args),
+        Some("-h") | Some("--help") | Some("help") => return Ok(Command::Help),
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+    }
+
+    let mut options = Options {
+        files: Vec::new(),
+        format: Format::Text,
+        output: None,
+        timeout: None,
+        quiet: false,
+        engine: Engine::default(),
+        branching: Branching::default(),
+        level: Level::default(),
+        solutions: 1,
+        stats: None,
+        threads: 1,
+        svg: None,
+        guides: false,
+        blank: false,
+        png: None,
+        pbm: None,
+        pgm: None,
+        scale: 1,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                options.format = match value(&mut args, &arg)?.as_str() {
+                    "text" => Format::Text,
+                    "terminal" => Format::Terminal,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            "-o" | "--output" => options.output = Some(value(&mut args, &arg)?),
+            "-t" | "--timeout" => options.timeout = Some(timeout(&mut args, &arg)?),
+            "-e" | "--engine" => {
+                options.engine = match value(&mut args, &arg)?.as_str() {
+                    "search" => Engine::Search,
+                    #[cfg(feature = "sat")]
+                    "sat" => Engine::Sat,
+                    #[cfg(not(feature = "sat"))]
+                    "sat" => {
+                        return Err("the sat engine needs a build with the sat feature".to_string())
+                    }
+                    other => return Err(format!("unknown engine '{}'", other)),
+                }
+            }
+            "-b" | "--branching" => {
+                let name = value(&mut args, &arg)?;
+                options.branching = BRANCHINGS
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, branching)| *branching)
+                    .ok_or_else(|| format!("unknown branching strategy '{}'", name))?;
+            }
+            "-l" | "--level" => {
+                options.level = match value(&mut args, &arg)?.as_str() {
+                    "line" => Level::LineSolving,
+                    "probe" => Level::Probing,
+                    other => return Err(format!("unknown level '{}'", other)),
+                }
+            }
+            "-n" | "--solutions" => {
+                let n = value(&mut args, &arg)?;
+                options.solutions = n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid number of solutions '{}'", n))?;
+            }
+            "-s" | "--stats" => {
+                options.stats = match value(&mut args, &arg)?.as_str() {
+                    "text" => Some(StatsFormat::Text),
+                    "json" => Some(StatsFormat::Json),
+                    other => return Err(format!("unknown stats format '{}'", other)),
+                }
+            }
+            "-j" | "--threads" => {
+                let n = value(&mut args, &arg)?;
+                options.threads = n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid number of threads '{}'", n))?;
+            }
+            "--svg" => options.svg = Some(value(&mut args, &arg)?),
+            "--guides" => options.guides = true,
+            "--blank" => options.blank = true,
+            "--png" => options.png = Some(value(&mut args, &arg)?),
+            "--pbm" => options.pbm = Some(value(&mut args, &arg)?),
+            "--pgm" => options.pgm = Some(value(&mut args, &arg)?),
+            "--scale" => {
+                let n = value(&mut args, &arg)?;
+                options.scale = n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid scale '{}'", n))?;
+            }
+            "-q" | "--quiet" => options.quiet = true,
+            "-h" | "--help" => return Ok(Command::Help),
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option '{}'", arg))
+            }
+            _ => options.files.push(arg),
+        }
+    }
+    if options.files.is_empty() {
+        return Err("no puzzle files given".to_string());
+    }
+    Ok(Command::Solve(options))
+}
+
+//Parses the arguments of the image command
+fn parse_image_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut file = None;
+    let mut output = None;
+    let mut threshold = 128;
+    let mut fix = None;
+    let mut time = Duration::from_secs(10);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-t" | "--timeout" => time = timeout(&mut args, &arg)?,
+            "--fix" => {
+                fix = match value(&mut args, &arg)?.as_str() {
+                    "givens" => Some(Fix::Givens),
+                    "flips" => Some(Fix::Flips),
+                    other => return Err(format!("unknown fix '{}'", other)),
+                }
+            }
+            "-o" | "--output" => output = Some(value(&mut args, &arg)?),
+            "--threshold" => {
+                let n = value(&mut args, &arg)?;
+                threshold = n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid threshold '{}'", n))?;
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option '{}'", arg))
+            }
+            _ if file.is_some() => return Err("image takes a single image file".to_string()),
+            _ => file = Some(arg),
+        }
+    }
+    match file {
+        Some(file) => Ok(Command::Image(ImageOptions {
+            file,
+            output,
+            threshold,
+            fix,
+            timeout: time,
+        })),
+        None => Err("no image file given".to_string()),
+    }
+}
+
+//Parses the arguments of the generate command
+fn parse_generate_args<I: Iterator<Item = String>>(mut args: I) -> Result<Command, String> {
+    let mut size = None;
+    let mut generate = GenerateArgs {
+        width: 0,
+        height: 0,
+        density: 0.5,
+        seed: None,
+        difficulty: None,
+        attempts: 100,
+        count: 1,
+        output: None,
+    };
+    let positive = |n: String, what: &str| {
+        n.parse()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("invalid {} '{}'", what, n))
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--density" => {
+                let d = value(&mut args, &arg)?;
+                generate.density = d
+                    .parse()
+                    .ok()
+                    .filter(|d| (0.0..=1.0).contains(d))
+                    .ok_or_else(|| format!("invalid density '{}'", d))?;
+            }
+            "--seed" => {
+                let n = value(&mut args, &arg)?;
+                let seed = n.parse().map_err(|_| format!("invalid seed '{}'", n))?;
+                generate.seed = Some(seed);
+            }
+            "--difficulty" => {
+                let name = value(&mut args, &arg)?;
+                let difficulty = DIFFICULTIES
+                    .iter()
+                    .find(|d| d.label() == name)
+                    .ok_or_else(|| format!("unknown difficulty '{}'", name))?;
+                generate.difficulty = Some(*difficulty);
+            }
+            "--attempts" => generate.attempts = positive(value(&mut args, &arg)?, "attempts")?,
+            "--count" => generate.count = positive(value(&mut args, &arg)?, "count")?,
+            "-o" | "--output" => generate.output = Some(value(&mut args, &arg)?),
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(format!("unknown option '{}'", arg))
+            }
+            _ if size.is_some() => return Err("generate takes a single size".to_string()),
+            _ => size = Some(arg),
+        }
+    }
+    let size = size.ok_or_else(|| "no puzzle size given".to_string())?;
+    //WIDTHxHEIGHT, or a single number for a square
+    let (width, height) = size.split_once('x').unwrap_or((&size, &size));
+    generate.width = positive(width.to_string(), "width")?;
+    generate.height = positive(height.to_string(), "height")?;
+    Ok(Command::Generate(generate))
+}
+
+const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Overlap,
+    Difficulty::LineSolving,
+    Difficulty::Probing,
+    Difficulty::Backtracking,
+];
+
+//Returns the value following an option
+fn value<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("option '{}' requires a value", option))
+}
+
+//Returns the number of seconds following an option, which must be positive and short enough
+//for a deadline that many seconds from now to exist
+fn timeout<I: Iterator<Item = String>>(args: &mut I, option: &str) -> Result<Duration, String> {
+    let seconds = value(args, option)?;
+    seconds
+        .parse()
+        .ok()
+        .filter(|s: &f64| *s > 0.0)
+        .and_then(|s| Duration::try_from_secs_f64(s).ok())
+        .filter(|&timeout| Instant::now().checked_add(timeout).is_some())
+        .ok_or_else(|| format!("invalid timeout '{}'", seconds))
+}
+
+#[cfg(test)]
+fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn no_arguments_is_interactive() {
+    assert_eq!(parse_args(args("")), Ok(Command::Interactive));
+}
+
+#[test]
+fn solve_with_options() {
+    let command = parse_args(args(
+        "solve a.txt -f terminal --timeout 2.5 -q b.txt -o out.txt -b probe -n 3 --stats json -l probe -j 4 -e search --svg a.svg --guides --png a.png --scale 4",
+    ));
+    assert_eq!(
+        command,
+        Ok(Command::Solve(Options {
+            files: vec!["a.txt".to_string(), "b.txt".to_string()],
+            format: Format::Terminal,
+            output: Some("out.txt".to_string()),
+            timeout: Some(Duration::from_millis(2500)),
+            quiet: true,
+            engine: Engine::Search,
+            branching: Branching::Probing,
+            level: Level::Probing,
+            solutions: 3,
+            stats: Some(StatsFormat::Json),
+            threads: 4,
+            svg: Some("a.svg".to_string()),
+            guides: true,
+            blank: false,
+            png: Some("a.png".to_string()),
+            pbm: None,
+            pgm: None,
+            scale: 4,
+        }))
+    );
+}
+
+#[test]
+fn json_format() {
+    assert!(matches!(
+        parse_args(args("solve a.json -f json")),
+        Ok(Command::Solve(Options {
+            format: Format::Json,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn explain_one_file() {
+    assert_eq!(
+        parse_args(args("explain a.txt")),
+        Ok(Command::Explain("a.txt".to_string()))
+    );
+}
+
+#[test]
+fn rate_files() {
+    assert_eq!(
+        parse_args(args("rate a.txt b.txt")),
+        Ok(Command::Rate(vec![
+            "a.txt".to_string(),
+            "b.txt".to_string()
+        ]))
+    );
+    assert!(parse_args(args("rate")).is_err());
+}
+
+#[test]
+fn image_options() {
+    assert_eq!(
+        parse_args(args(
+            "image cat.png --threshold 100 -o cat.json --fix givens"
+        )),
+        Ok(Command::Image(ImageOptions {
+            file: "cat.png".to_string(),
+            output: Some("cat.json".to_string()),
+            threshold: 100,
+            fix: Some(Fix::Givens),
+            timeout: Duration::from_secs(10),
+        }))
+    );
+    assert!(matches!(
+        parse_args(args("image a.png -t 0.5")),
+        Ok(Command::Image(ImageOptions { timeout, .. })) if timeout == Duration::from_millis(500)
+    ));
+    assert!(parse_args(args("image a.png --timeout 0")).is_err());
+    assert!(parse_args(args("image a.png -t 1e19")).is_err());
+    assert!(parse_args(args("image a.png --fix pixels")).is_err());
+    assert!(parse_args(args("image")).is_err());
+    assert!(parse_args(args("image a.png b.png")).is_err());
+    assert!(parse_args(args("image a.png --threshold 0")).is_err());
+    assert!(parse_args(args("image a.png --threshold 256")).is_err());
+}
+
+#[test]
+fn generate_options() {
+    assert_eq!(
+        parse_args(args(
+            "generate 15x10 --density 0.6 --seed 42 --difficulty hard --count 5 -o pack.txt"
+        )),
+        Ok(Command::Generate(GenerateArgs {
+            width: 15,
+            height: 10,
+            density: 0.6,
+            seed: Some(42),
+            difficulty: Some(Difficulty::Probing),
+            attempts: 100,
+            count: 5,
+            output: Some("pack.txt".to_string()),
+        }))
+    );
+    assert!(matches!(
+        parse_args(args("generate 12")),
+        Ok(Command::Generate(GenerateArgs {
+            width: 12,
+            height: 12,
+            seed: None,
+            ..
+        }))
+    ));
+    assert!(parse_args(args("generate")).is_err());
+    assert!(parse_args(args("generate 0x5")).is_err());
+    assert!(parse_args(args("generate 5x")).is_err());
+    assert!(parse_args(args("generate 5 --density 1.5")).is_err());
+    assert!(parse_args(args("generate 5 --difficulty impossible")).is_err());
+    assert!(parse_args(args("generate 5 --seed -1")).is_err());
+}
+
+#[test]
+fn invalid_arguments() {
+    assert!(parse_args(args("solve")).is_err());
+    assert!(parse_args(args("solve a.txt --format")).is_err());
+    assert!(parse_args(args("solve a.txt --format html")).is_err());
+    assert!(parse_args(args("solve a.txt --timeout -1")).is_err());
+    //too long to be represented, or to add to the current time
+    assert!(parse_args(args("solve a.txt --timeout 1e30")).is_err());
+    assert!(parse_args(args("solve a.txt --timeout 1e19")).is_err());
+    assert!(parse_args(args("solve a.txt --bogus")).is_err());
+    assert!(parse_args(args("solve a.txt --branching random")).is_err());
+    assert!(parse_args(args("compare")).is_err());
+    assert!(parse_args(args("explain")).is_err());
+    assert!(parse_args(args("explain a.txt b.txt")).is_err());
+    assert!(parse_args(args("solve a.txt -n 0")).is_err());
+    assert!(parse_args(args("solve a.txt --stats xml")).is_err());
+    assert!(parse_args(args("solve a.txt --level guess")).is_err());
+    assert!(parse_args(args("solve a.txt -j 0")).is_err());
+    assert!(parse_args(args("solve a.txt --engine dfs")).is_err());
+    assert!(parse_args(args("solve a.txt --svg")).is_err());
+    assert!(parse_args(args("solve a.txt --pbm")).is_err());
+    assert!(parse_args(args("solve a.txt --png a.png --scale 0")).is_err());
+    assert!(parse_args(args("bogus")).is_err());
+}
+
+#[test]
+fn worst_outcome_wins() {
+    let outcomes = [
+        Outcome::Solved,
+        Outcome::Timeout,
+        Outcome::NotUnique,
+        Outcome::Unsolvable,
+    ];
+    assert_eq!(outcomes.iter().max(), Some(&Outcome::Timeout));
+}
@@ -0,0 +1,727 @@
+use crate::parse::{read_color_nonogram, ParseError};
+use crate::propagate::WorkQueue;
+use crate::solver::{Limits, SolveOutcome, SolveStats};
+use crate::validate::{Line, ValidationError};
+use std::fmt;
+use std::path::Path;
+use std::time::Instant;
+
+//Largest number of colors in a palette, each cell keeps its possible colors as bits of a u32
+//alongside the background
+pub(crate) const MAX_COLORS: usize = 31;
+
+///A paint color of a colored puzzle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Color {
+    pub name: String,
+    ///Character standing for the color in clues and text output
+    pub symbol: char,
+    ///Red, green and blue components, used to draw the color in a terminal
+    pub rgb: [u8; 3],
+}
+
+///A clue of a colored puzzle: a block of length cells painted in the palette color of the
+///given index. Blocks of different colors may touch, blocks of the same color need a gap.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ColorClue {
+    pub length: usize,
+    pub color: usize,
+}
+
+///The clues of a colored nonogram, where each cell is either left as background or painted in
+///one of the colors of a palette
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorPuzzle {
+    palette: Vec<Color>,
+    rows: Vec<Vec<ColorClue>>,
+    columns: Vec<Vec<ColorClue>>,
+}
+
+///A filled in grid of a [`ColorPuzzle`], each cell holding the index of its color in the
+///palette or None for the background, or the partly filled in grid of a
+///[`SolveOutcome::Timeout`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorSolution {
+    palette: Vec<Color>,
+    cells: Vec<Vec<Option<usize>>>,
+    //cells a partial grid leaves undecided, in row-major order
+    unknown: Vec<(usize, usize)>,
+}
+
+impl ColorPuzzle {
+    ///Creates a puzzle from its palette and the clues of each row (top to bottom) and column
+    ///(left to right). Clues of length 0 are dropped.
+    pub fn new(
+        palette: Vec<Color>,
+        rows: Vec<Vec<ColorClue>>,
+        columns: Vec<Vec<ColorClue>>,
+    ) -> ColorPuzzle {
+        let drop_zeros = |lines: Vec<Vec<ColorClue>>| -> Vec<Vec<ColorClue>> {
+            lines
+                .into_iter()
+                .map(|clues| clues.into_iter().filter(|c| c.length > 0).collect())
+                .collect()
+        };
+        ColorPuzzle {
+            palette,
+            rows: drop_zeros(rows),
+            columns: drop_zeros(columns),
+        }
+    }
+
+    ///Reads a puzzle from a file, see [`crate::parse_color_nonogram`]
+    pub fn from_file<P: AsRef<Path>>(file: P) -> Result<ColorPuzzle, ParseError> {
+        read_color_nonogram(file)
+    }
+
+    ///Number of columns in the grid
+    pub fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    ///Number of rows in the grid
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn palette(&self) -> &[Color] {
+        &self.palette
+    }
+
+    ///The clues of each row, top to bottom
+    pub fn row_clues(&self) -> &[Vec<ColorClue>] {
+        &self.rows
+    }
+
+    ///The clues of each column, left to right
+    pub fn column_clues(&self) -> &[Vec<ColorClue>] {
+        &self.columns
+    }
+
+    ///Checks that the clues can describe a grid: every clue has a color of the palette, fits
+    ///in its line, and the rows paint as many cells of each color as the columns
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let (width, height) = (self.width(), self.height());
+        if width == 0 || height == 0 {
+            return Err(vec![ValidationError::EmptyGrid { width, height }]);
+        }
+        if self.palette.len() > MAX_COLORS {
+            return Err(vec![ValidationError::TooManyColors {
+                colors: self.palette.len(),
+                max: MAX_COLORS,
+            }]);
+        }
+        let mut errors = Vec::new();
+        let lines = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, clues)| (Line::Row(i), clues, width))
+            .chain(
+                self.columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, clues)| (Line::Column(i), clues, height)),
+            );
+        for (line, clues, length) in lines {
+            if let Some(clue) = clues.iter().find(|c| c.color >= self.palette.len()) {
+                errors.push(ValidationError::UnknownColor {
+                    line,
+                    color: clue.color,
+                });
+                continue;
+            }
+            let needed = needed_cells(clues);
+            if needed > length {
+                errors.push(ValidationError::ClueTooLong {
+                    line,
+                    needed,
+                    length,
+                });
+            }
+        }
+        if errors.is_empty() {
+            for (color, paint) in self.palette.iter().enumerate() {
+                let total = |lines: &[Vec<ColorClue>]| -> usize {
+                    lines
+                        .iter()
+                        .flatten()
+                        .filter(|c| c.color == color)
+                        .map(|c| c.length)
+                        .sum()
+                };
+                let (rows, columns) = (total(&self.rows), total(&self.columns));
+                if rows != columns {
+                    errors.push(ValidationError::ColorMismatch {
+                        color: paint.name.clone(),
+                        rows,
+                        columns,
+                    });
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    ///Solves the puzzle, returning None if the clues are invalid or no grid satisfies them
+    pub fn solve(&self) -> Option<ColorSolution> {
+        self.solutions(1).pop()
+    }
+
+    ///Solves the puzzle, giving up once one of the limits is reached. A puzzle whose clues
+    ///fail [`ColorPuzzle::validate`] is reported as [`SolveOutcome::Unsat`].
+    pub fn solve_with_limits(&self, limits: Limits) -> SolveOutcome<ColorSolution> {
+        let (mut solutions, partial, _) = self.solutions_with_limits(&limits, 1);
+        match (solutions.pop(), partial) {
+            (Some(solution), _) => SolveOutcome::Solved(solution),
+            (None, Some(partial)) => SolveOutcome::Timeout(partial),
+            (None, None) => SolveOutcome::Unsat,
+        }
+    }
+
+    ///Returns up to limit distinct solutions of the puzzle
+    pub fn solutions(&self, limit: usize) -> Vec<ColorSolution> {
+        self.solutions_with_limits(&Limits::default(), limit).0
+    }
+
+    ///Returns up to limit distinct solutions, giving up once one of the limits is reached,
+    ///along with the counters collected while searching for them. If the limits stop the
+    ///search, the solutions found so far are returned with the cells line solving fixed
+    ///before the first guess, as in [`SolveOutcome::Timeout`]; the partial solution is None
+    ///when the search ran to the end.
+    pub fn solutions_with_limits(
+        &self,
+        limits: &Limits,
+        limit: usize,
+    ) -> (Vec<ColorSolution>, Option<ColorSolution>, SolveStats) {
+        let mut stats = SolveStats::default();
+        if limit == 0 || self.validate().is_err() {
+            return (Vec::new(), None, stats);
+        }
+        let start = Instant::now();
+        //the background and every color, shifted so that 31 colors fill all 32 bits
+        let full = u32::MAX >> (MAX_COLORS - self.palette.len());
+        let grid = ColorGrid {
+            width: self.width(),
+            cells: vec![full; self.width() * self.height()],
+        };
+        let (grids, partial) = self.search(grid, limit, limits, &mut stats);
+        stats.elapsed = start.elapsed();
+        let solutions = grids
+            .iter()
+            .map(|grid| grid.solution(&self.palette))
+            .collect();
+        let partial = partial.map(|grid| grid.solution(&self.palette));
+        (solutions, partial, stats)
+    }
+
+    ///Counts the solutions of the puzzle, stopping once limit solutions are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions(limit).len()
+    }
+
+    ///Returns true if the puzzle has exactly one solution
+    pub fn is_unique(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    //Solves the queued lines until nothing changes, returning false on a contradiction
+    fn propagate(
+        &self,
+        grid: &mut ColorGrid,
+        queue: &mut WorkQueue,
+        stats: &mut SolveStats,
+    ) -> bool {
+        while let Some(line) = queue.pop() {
+            stats.lines_solved += 1;
+            let (clues, cells) = match line {
+                Line::Row(i) => (&self.rows[i], grid.row(i)),
+                Line::Column(j) => (&self.columns[j], grid.column(j)),
+            };
+            let solved = match solve_color_line(clues, &cells) {
+                Some(solved) => solved,
+                None => return false,
+            };
+            for (n, &domain) in solved.iter().enumerate() {
+                if domain == cells[n] {
+                    continue;
+                }
+                let (i, j, crossing) = match line {
+                    Line::Row(i) => (i, n, Line::Column(n)),
+                    Line::Column(j) => (n, j, Line::Row(n)),
+                };
+                grid.cells[i * grid.width + j] = domain;
+                if domain.count_ones() == 1 {
+                    stats.fixed.line_solver += 1;
+                }
+                queue.push(crossing);
+            }
+        }
+        true
+    }
+
+    //Backtracking search: propagates, then tries each color left for the cell with the fewest,
+    //collecting up to limit complete grids. Limits are checked before each guess; once one is
+    //reached, the grid after the first propagation is returned as the partial solution.
+    fn search(
+        &self,
+        grid: ColorGrid,
+        limit: usize,
+        limits: &Limits,
+        stats: &mut SolveStats,
+    ) -> (Vec<ColorGrid>, Option<ColorGrid>) {
+        let (height, width) = (self.height(), self.width());
+        let mut solutions = Vec::new();
+        let mut root = None;
+        let mut stack = vec![(grid, WorkQueue::all(height, width), 0)];
+        while let Some((mut grid, mut queue, depth)) = stack.pop() {
+            if !self.propagate(&mut grid, &mut queue, stats) {
+                if depth > 0 {
+                    stats.backtracks += 1;
+                }
+                continue;
+            }
+            if depth == 0 {
+                root = Some(grid.clone());
+            }
+            let cell = (0..grid.cells.len())
+                .filter(|&c| grid.cells[c].count_ones() > 1)
+                .min_by_key(|&c| grid.cells[c].count_ones());
+            let cell = match cell {
+                Some(cell) => cell,
+                None => {
+                    solutions.push(grid);
+                    if solutions.len() == limit {
+                        break;
+                    }
+                    continue;
+                }
+            };
+            if limits.reached(stats.nodes) {
+                stats.interrupted = true;
+                return (solutions, root);
+            }
+            stats.guesses += 1;
+            stats.max_depth = stats.max_depth.max(depth + 1);
+            //pushed in reverse so the background is tried last
+            let domain = grid.cells[cell];
+            for bit in (0..32).filter(|&bit| domain & (1 << bit) != 0) {
+                let mut guess = grid.clone();
+                guess.cells[cell] = 1 << bit;
+                let mut queue = WorkQueue::new(height, width);
+                queue.push(Line::Row(cell / width));
+                queue.push(Line::Column(cell % width));
+                stats.nodes += 1;
+                stack.push((guess, queue, depth + 1));
+            }
+        }
+        (solutions, None)
+    }
+}
+
+//cells needed by the clues of a line: their lengths plus a gap between blocks of one color
+pub(crate) fn needed_cells(clues: &[ColorClue]) -> usize {
+    let gaps = clues
+        .windows(2)
+        .filter(|pair| pair[0].color == pair[1].color)
+        .count();
+    //saturates, so that clues too long to count never fit
+    clues
+        .iter()
+        .fold(gaps, |sum, c| sum.saturating_add(c.length))
+}
+
+//The possible states of every cell, bit 0 for the background and bit c + 1 for color c
+#[derive(Debug, Clone)]
+struct ColorGrid {
+    width: usize,
+    cells: Vec<u32>,
+}
+
+impl ColorGrid {
+    fn row(&self, i: usize) -> Vec<u32> {
+        self.cells[i * self.width..(i + 1) * self.width].to_vec()
+    }
+
+    fn column(&self, j: usize) -> Vec<u32> {
+        self.cells
+            .iter()
+            .skip(j)
+            .step_by(self.width)
+            .copied()
+            .collect()
+    }
+
+    //the color of every decided cell, the others left unknown
+    fn solution(&self, palette: &[Color]) -> ColorSolution {
+        let mut unknown = Vec::new();
+        let mut cells = Vec::new();
+        for (i, row) in self.cells.chunks(self.width).enumerate() {
+            let mut colors = Vec::with_capacity(row.len());
+            for (j, &domain) in row.iter().enumerate() {
+                if domain.count_ones() > 1 {
+                    unknown.push((i, j));
+                    colors.push(None);
+                    continue;
+                }
+                colors.push(match domain.trailing_zeros() {
+                    0 => None,
+                    bit => Some(bit as usize - 1),
+                });
+            }
+            cells.push(colors);
+        }
+        ColorSolution {
+            palette: palette.to_vec(),
+            cells,
+            unknown,
+        }
+    }
+}
+
+///Solves a single row or column of a colored puzzle exactly. Each cell is given as the set of
+///states it can still take, bit 0 for the background and bit c + 1 for color c, and comes back
+///narrowed to the states some placement of the clues consistent with the line gives it.
+///Returns None if no placement is consistent.
+pub(crate) fn solve_color_line(clues: &[ColorClue], cells: &[u32]) -> Option<Vec<u32>> {
+    let (n, k) = (cells.len(), clues.len());
+    let background = |p: usize| cells[p] & 1 != 0;
+    //run[c][p]: number of cells from p on that can take color c
+    let runs = |bit: u32| -> Vec<usize> {
+        let mut run = vec![0; n + 1];
+        for p in (0..n).rev() {
+            run[p] = if cells[p] & bit != 0 {
+                run[p + 1] + 1
+            } else {
+                0
+            };
+        }
+        run
+    };
+    let blank = runs(1);
+    let clue_runs: Vec<Vec<usize>> = clues.iter().map(|c| runs(1 << (c.color + 1))).collect();
+    //cells that must separate clue t - 1 from clue t
+    let gap = |t: usize| usize::from(t > 0 && t < k && clues[t - 1].color == clues[t].color);
+    //clue t can cover cells s..s + length
+    let fits = |t: usize, s: usize| s + clues[t].length <= n && clue_runs[t][s] >= clues[t].length;
+    //separation of g cells ending before p can be background
+    let separated = |p: usize, g: usize| g == 0 || (p >= g && background(p - 1));
+
+    //fwd[t][p]: the first t clues fit in cells 0..p
+    let mut fwd = vec![vec![false; n + 1]; k + 1];
+    for (p, fits) in fwd[0].iter_mut().enumerate() {
+        *fits = blank[0] >= p;
+    }
+    for t in 1..=k {
+        let length = clues[t - 1].length;
+        for p in 1..=n {
+            fwd[t][p] = (fwd[t][p - 1] && background(p - 1))
+                || (p >= length && {
+                    let s = p - length;
+                    let g = gap(t - 1);
+                    fits(t - 1, s) && separated(s, g) && fwd[t - 1][s - g.min(s)]
+                });
+        }
+    }
+    if !fwd[k][n] {
+        return None;
+    }
+    //bwd[t][p]: clues t.. fit in cells p..n
+    let mut bwd = vec![vec![false; n + 1]; k + 1];
+    for (p, fits) in bwd[k].iter_mut().enumerate() {
+        *fits = blank[p] >= n - p;
+    }
+    for t in (0..k).rev() {
+        let length = clues[t].length;
+        for p in (0..n).rev() {
+            let e = p + length;
+            let g = gap(t + 1);
+            bwd[t][p] = (background(p) && bwd[t][p + 1])
+                || (fits(t, p) && e + g <= n && separated(e + g, g) && bwd[t + 1][e + g]);
+        }
+    }
+
+    let mut solved = vec![0; n];
+    for (p, state) in solved.iter_mut().enumerate() {
+        if background(p) && (0..=k).any(|t| fwd[t][p] && bwd[t][p + 1]) {
+            *state |= 1;
+        }
+    }
+    for (t, clue) in clues.iter().enumerate() {
+        let (before, after) = (gap(t), gap(t + 1));
+        for s in 0..n.saturating_sub(clue.length - 1) {
+            let e = s + clue.length;
+            if fits(t, s)
+                && separated(s, before)
+                && fwd[t][s - before.min(s)]
+                && e + after <= n
+                && separated(e + after, after)
+                && bwd[t + 1][e + after]
+            {
+                for state in solved[s..e].iter_mut() {
+                    *state |= 1 << (clue.color + 1);
+                }
+            }
+        }
+    }
+    Some(solved)
+}
+
+impl ColorSolution {
+    //Creates a solution from rows of cell colors read elsewhere, e.g. the picture of a file
+    pub(crate) fn from_rows(palette: Vec<Color>, cells: Vec<Vec<Option<usize>>>) -> ColorSolution {
+        ColorSolution {
+            palette,
+            cells,
+            unknown: Vec::new(),
+        }
+    }
+
+    ///Number of columns in the grid
+    pub fn width(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    ///Number of rows in the grid
+    pub fn height(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn palette(&self) -> &[Color] {
+        &self.palette
+    }
+
+    ///Index in the palette of the color of a cell, None for the background or a cell a partial
+    ///grid leaves unknown
+    pub fn cell(&self, row: usize, column: usize) -> Option<usize> {
+        self.cells[row][column]
+    }
+
+    ///Returns false for the cells a partial grid leaves unknown, see [`SolveOutcome::Timeout`]
+    pub fn is_known(&self, row: usize, column: usize) -> bool {
+        self.unknown.binary_search(&(row, column)).is_err()
+    }
+
+    ///The color of a cell, None for the background
+    pub fn color(&self, row: usize, column: usize) -> Option<&Color> {
+        self.cell(row, column).map(|c| &self.palette[c])
+    }
+
+    ///Returns the (row, column) of every cell that differs between the two solutions
+    pub fn differences(&self, other: &ColorSolution) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for i in 0..self.height().min(other.height()) {
+            for j in 0..self.width().min(other.width()) {
+                if self.cell(i, j) != other.cell(i, j) {
+                    cells.push((i, j));
+                }
+            }
+        }
+        cells
+    }
+
+    ///The grid as rows of cell colors, top to bottom
+    pub fn rows(&self) -> &[Vec<Option<usize>>] {
+        &self.cells
+    }
+}
+
+///Writes the grid one row per line, with the symbol of each color, '.' for the background and
+///'?' for unknown cells
+impl fmt::Display for ColorSolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.cells.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let c = match cell {
+                    _ if !self.is_known(i, j) => '?',
+                    Some(c) => self.palette[*c].symbol,
+                    None => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn palette() -> Vec<Color> {
+    vec![
+        Color {
+            name: "red".to_string(),
+            symbol: 'r',
+            rgb: [255, 0, 0],
+        },
+        Color {
+            name: "green".to_string(),
+            symbol: 'g',
+            rgb: [0, 160, 0],
+        },
+    ]
+}
+
+#[cfg(test)]
+fn clues(text: &str) -> Vec<ColorClue> {
+    text.split_whitespace()
+        .map(|clue| {
+            let (length, color) = clue.split_at(clue.len() - 1);
+            ColorClue {
+                length: length.parse().unwrap(),
+                color: if color == "r" { 0 } else { 1 },
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn different_colors_need_no_gap() {
+    let all = vec![0b111; 4];
+    //2 red and 2 green fill 4 cells exactly
+    assert_eq!(
+        solve_color_line(&clues("2r 2g"), &all),
+        Some(vec![0b010, 0b010, 0b100, 0b100])
+    );
+    //two reds do not fit in 4 cells with a gap
+    assert_eq!(solve_color_line(&clues("2r 2r"), &all), None);
+    //a red 1 then a green 1 in 3 cells: the middle cell can be either color or background
+    let solved = solve_color_line(&clues("1r 1g"), &[0b111; 3]).unwrap();
+    assert_eq!(solved, vec![0b011, 0b111, 0b101]);
+    //no clues leaves only the background
+    assert_eq!(solve_color_line(&[], &all), Some(vec![0b001; 4]));
+}
+
+#[test]
+fn solve_colored_puzzle() {
+    // rg
+    // r.
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![clues("1r 1g"), clues("1r")],
+        vec![clues("2r"), clues("1g")],
+    );
+    assert_eq!(puzzle.validate(), Ok(()));
+    let solution = puzzle.solve().unwrap();
+    assert_eq!(solution.to_string(), "rg\nr.\n");
+    assert_eq!(solution.color(0, 1).unwrap().name, "green");
+    assert_eq!(solution.cell(1, 1), None);
+    assert!(puzzle.is_unique());
+
+    //the order of the clues decides which color goes first in each line
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![clues("1r 1g"), clues("1g 1r")],
+        vec![clues("1r 1g"), clues("1g 1r")],
+    );
+    let solutions = puzzle.solutions(5);
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(solutions[0].to_string(), "rg\ngr\n");
+
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![clues("1r"), clues("1r")],
+        vec![clues("1r"), clues("1r")],
+    );
+    assert_eq!(puzzle.count_solutions(5), 2);
+    let limits = Limits {
+        max_nodes: Some(0),
+        ..Default::default()
+    };
+    let (solutions, partial, stats) = puzzle.solutions_with_limits(&limits, 2);
+    assert!(solutions.is_empty() && stats.interrupted);
+    assert_eq!(partial.unwrap().to_string(), "??\n??\n");
+    let (solutions, partial, stats) = puzzle.solutions_with_limits(&Limits::default(), 5);
+    assert_eq!(solutions.len(), 2);
+    assert!(partial.is_none() && !stats.interrupted);
+    assert_eq!((stats.guesses, stats.nodes), (1, 2));
+    assert!(matches!(
+        puzzle.solve_with_limits(limits),
+        SolveOutcome::Timeout(_)
+    ));
+}
+
+#[test]
+fn solve_with_every_color() {
+    //a row of one cell of each color, as many colors as a palette can hold
+    let symbols = "abcdefghijklmnopqrstuvwxyzABCDE";
+    let palette: Vec<Color> = symbols
+        .chars()
+        .enumerate()
+        .map(|(c, symbol)| Color {
+            name: format!("color {}", c),
+            symbol,
+            rgb: [c as u8; 3],
+        })
+        .collect();
+    assert_eq!(palette.len(), MAX_COLORS);
+    let row = (0..MAX_COLORS)
+        .map(|color| ColorClue { length: 1, color })
+        .collect();
+    let columns = (0..MAX_COLORS)
+        .map(|color| vec![ColorClue { length: 1, color }])
+        .collect();
+    let puzzle = ColorPuzzle::new(palette, vec![row], columns);
+    assert_eq!(puzzle.validate(), Ok(()));
+    let solution = puzzle.solve().unwrap();
+    assert_eq!(solution.to_string(), format!("{}\n", symbols));
+    assert!(puzzle.is_unique());
+}
+
+#[test]
+fn colored_clues_are_validated() {
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![clues("1r 1r"), clues("1g")],
+        vec![clues("1r"), clues("1r")],
+    );
+    assert_eq!(
+        puzzle.validate(),
+        Err(vec![ValidationError::ClueTooLong {
+            line: Line::Row(0),
+            needed: 3,
+            length: 2
+        }])
+    );
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![clues("1r 1g"), clues("1g")],
+        vec![clues("1r"), clues("1g")],
+    );
+    assert_eq!(
+        puzzle.validate(),
+        Err(vec![ValidationError::ColorMismatch {
+            color: "green".to_string(),
+            rows: 2,
+            columns: 1
+        }])
+    );
+    let puzzle = ColorPuzzle::new(
+        palette(),
+        vec![vec![ColorClue {
+            length: 1,
+            color: 2,
+        }]],
+        vec![clues("1r")],
+    );
+    assert_eq!(
+        puzzle.validate(),
+        Err(vec![ValidationError::UnknownColor {
+            line: Line::Row(0),
+            color: 2
+        }])
+    );
+}
+
+#[test]
+fn read_colored_puzzle_file() {
+    let puzzle = ColorPuzzle::from_file("./src/puzzles/color1.txt").unwrap();
+    assert_eq!(puzzle.palette().len(), 3);
+    assert!(puzzle.is_unique());
+    let solution = puzzle.solve().unwrap();
+    assert_eq!(solution.color(0, 5).unwrap().name, "green");
+    assert_eq!(solution.color(4, 3).unwrap().symbol, 'y');
+}
@@ -0,0 +1,199 @@
+use crate::rate::{rate_within, Difficulty, Rating};
+use crate::solver::{Limits, SolveOptions};
+use crate::{Puzzle, Solution};
+
+///Settings for [`generate_puzzle`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GenerateOptions {
+    pub width: usize,
+    pub height: usize,
+    ///Share of the cells marked in the random grids, from 0 to 1
+    pub density: f64,
+    ///Difficulty the puzzle must have, any if None
+    pub difficulty: Option<Difficulty>,
+    ///Random grids tried before giving up
+    pub attempts: usize,
+    ///Nodes the searches of an attempt may explore in all, see [`Limits::max_nodes`]. An attempt
+    ///that runs out of them is given up for the next one.
+    pub max_nodes: usize,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> GenerateOptions {
+        GenerateOptions {
+            width: 10,
+            height: 10,
+            density: 0.5,
+            difficulty: None,
+            attempts: 100,
+            max_nodes: 1000,
+        }
+    }
+}
+
+///A puzzle made by [`generate_puzzle`], with the grid it was drawn from
+#[derive(Debug, Clone)]
+pub struct GeneratedPuzzle {
+    pub puzzle: Puzzle,
+    ///The only solution of the puzzle
+    pub solution: Solution,
+    pub rating: Rating,
+    ///Random grids tried, including the one the puzzle came from
+    pub attempts: usize,
+}
+
+//SplitMix64, small and fixed so that a seed gives the same puzzles in every build
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    //true with the given probability
+    fn chance(&mut self, p: f64) -> bool {
+        ((self.next() >> 11) as f64) / ((1u64 << 53) as f64) < p
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+///Generates a random puzzle with exactly one solution. Each attempt fills a grid at random with
+///the given density; while its clues allow more than one solution, one of the cells where two
+///solutions disagree is flipped, which usually takes a few flips. A unique puzzle that does
+///not have the target difficulty starts a new attempt, as does a grid the solver cannot rate
+///or tell apart from others within `options.max_nodes` search nodes in all. Returns None if
+///no attempt succeeds.
+///
+///The same seed and options always give the same puzzle.
+pub fn generate_puzzle(options: &GenerateOptions, seed: u64) -> Option<GeneratedPuzzle> {
+    let mut rng = Rng(seed);
+    let (width, height) = (options.width, options.height);
+    if width == 0 || height == 0 {
+        return None;
+    }
+    for attempt in 1..=options.attempts {
+        let mut picture: Vec<Vec<bool>> = (0..height)
+            .map(|_| (0..width).map(|_| rng.chance(options.density)).collect())
+            .collect();
+        //flips draw from their own generator, so that an attempt cut short by the budget does
+        //not change the grids of the next ones
+        let mut flips = Rng(rng.next());
+        //nodes left to the searches of this attempt
+        let mut budget = options.max_nodes;
+        for _ in 0..=width * height {
+            let puzzle = Puzzle::from_picture(&picture);
+            let limits = Limits {
+                max_nodes: Some(budget),
+                ..Default::default()
+            };
+            let search = SolveOptions {
+                limits: limits.clone(),
+                ..Default::default()
+            };
+            //a grid the budget left cannot settle gives up the attempt
+            let (solutions, partial, stats) = puzzle.solutions_outcome(&search, 2);
+            if partial.is_some() {
+                break;
+            }
+            budget = budget.saturating_sub(stats.nodes);
+            if solutions.len() > 1 {
+                let differences = solutions[0].differences(&solutions[1]);
+                let (i, j) = differences[flips.below(differences.len())];
+                picture[i][j] = !picture[i][j];
+                continue;
+            }
+            //only unique grids are rated, probing every cell of each ambiguous one is too slow
+            let mut interrupted = false;
+            let rating = match rate_within(&puzzle, &limits, &mut interrupted) {
+                Some(rating) => rating,
+                None if interrupted => break,
+                None => return None,
+            };
+            if options.difficulty.is_some_and(|d| d != rating.difficulty) {
+                break;
+            }
+            let solution = Solution::from_picture(&puzzle, &picture);
+            return Some(GeneratedPuzzle {
+                puzzle,
+                solution,
+                rating,
+                attempts: attempt,
+            });
+        }
+    }
+    None
+}
+
+#[test]
+fn generate_unique_puzzles() {
+    let options = GenerateOptions {
+        width: 8,
+        height: 6,
+        ..Default::default()
+    };
+    let first = generate_puzzle(&options, 7).unwrap();
+    assert_eq!((first.puzzle.width(), first.puzzle.height()), (8, 6));
+    assert!(first.puzzle.is_unique());
+    assert_eq!(
+        first.puzzle.solve().unwrap().to_string(),
+        first.solution.to_string()
+    );
+    //the same seed gives the same puzzle
+    let again = generate_puzzle(&options, 7).unwrap();
+    assert_eq!(again.puzzle.row_clues(), first.puzzle.row_clues());
+    assert_eq!(again.puzzle.column_clues(), first.puzzle.column_clues());
+
+    for difficulty in [Difficulty::LineSolving, Difficulty::Probing] {
+        let options = GenerateOptions {
+            width: 10,
+            height: 10,
+            difficulty: Some(difficulty),
+            attempts: 1000,
+            ..Default::default()
+        };
+        let generated = generate_puzzle(&options, 1).unwrap();
+        assert_eq!(generated.rating.difficulty, difficulty);
+        assert!(generated.puzzle.is_unique());
+    }
+
+    let empty = GenerateOptions {
+        density: 0.0,
+        ..Default::default()
+    };
+    let generated = generate_puzzle(&empty, 3).unwrap();
+    assert_eq!(generated.rating.difficulty, Difficulty::Overlap);
+    assert_eq!(generated.attempts, 1);
+
+    //with no nodes to search, attempts whose grid needs a search are skipped
+    let options = GenerateOptions {
+        max_nodes: 0,
+        attempts: 1000,
+        ..Default::default()
+    };
+    let generated = generate_puzzle(&options, 1).unwrap();
+    let unbounded = GenerateOptions {
+        max_nodes: usize::MAX,
+        ..options
+    };
+    assert!(generated.attempts > generate_puzzle(&unbounded, 1).unwrap().attempts);
+    assert!(generated.puzzle.is_unique());
+    assert_ne!(generated.rating.difficulty, Difficulty::Backtracking);
+    let (_, partial, _) = generated.puzzle.solutions_outcome(
+        &SolveOptions {
+            limits: Limits {
+                max_nodes: Some(0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        2,
+    );
+    assert!(partial.is_none());
+}
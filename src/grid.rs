@@ -0,0 +1,208 @@
+use crate::board::Status;
+
+const BITS: usize = 64;
+
+//The cells of a board packed two bits each: one bitset of known cells and one of marked
+//cells, each row starting on a new word. Every change is logged on a trail so a guess can be
+//undone by rolling the trail back instead of copying the grid.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Grid {
+    height: usize,
+    width: usize,
+    words: usize,
+    known: Vec<u64>,
+    marked: Vec<u64>,
+    trail: Vec<(u32, u32, Status)>,
+}
+
+impl Grid {
+    //A grid with every cell unknown
+    pub(crate) fn new(height: usize, width: usize) -> Grid {
+        let words = width.div_ceil(BITS);
+        Grid {
+            height,
+            width,
+            words,
+            known: vec![0; height * words],
+            marked: vec![0; height * words],
+            trail: Vec::new(),
+        }
+    }
+
+    //A grid with every cell set to state
+    #[cfg(test)]
+    pub(crate) fn filled(height: usize, width: usize, state: Status) -> Grid {
+        let mut grid = Grid::new(height, width);
+        for i in 0..height {
+            for j in 0..width {
+                grid.set(i, j, state);
+            }
+        }
+        grid.trail.clear();
+        grid
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        self.height
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.width
+    }
+
+    //word index and bit mask of a cell
+    fn locate(&self, i: usize, j: usize) -> (usize, u64) {
+        debug_assert!(i < self.height && j < self.width);
+        (i * self.words + j / BITS, 1 << (j % BITS))
+    }
+
+    pub(crate) fn get(&self, i: usize, j: usize) -> Status {
+        let (w, bit) = self.locate(i, j);
+        if self.known[w] & bit == 0 {
+            Status::Unknown
+        } else if self.marked[w] & bit != 0 {
+            Status::Marked
+        } else {
+            Status::Empty
+        }
+    }
+
+    //Sets a cell, logging its previous state on the trail
+    pub(crate) fn set(&mut self, i: usize, j: usize, state: Status) {
+        let old = self.get(i, j);
+        if old != state {
+            self.trail.push((i as u32, j as u32, old));
+            self.write(i, j, state);
+        }
+    }
+
+    fn write(&mut self, i: usize, j: usize, state: Status) {
+        let (w, bit) = self.locate(i, j);
+        match state {
+            Status::Unknown => {
+                self.known[w] &= !bit;
+                self.marked[w] &= !bit;
+            }
+            Status::Empty => {
+                self.known[w] |= bit;
+                self.marked[w] &= !bit;
+            }
+            Status::Marked => {
+                self.known[w] |= bit;
+                self.marked[w] |= bit;
+            }
+        }
+    }
+
+    //Position on the trail to roll back to with undo
+    pub(crate) fn checkpoint(&self) -> usize {
+        self.trail.len()
+    }
+
+    //Restores every cell changed since the checkpoint
+    pub(crate) fn undo(&mut self, checkpoint: usize) {
+        while self.trail.len() > checkpoint {
+            let (i, j, old) = self.trail.pop().unwrap();
+            self.write(i as usize, j as usize, old);
+        }
+    }
+
+    //A copy of the cells without the trail
+    pub(crate) fn snapshot(&self) -> Grid {
+        Grid {
+            height: self.height,
+            width: self.width,
+            words: self.words,
+            known: self.known.clone(),
+            marked: self.marked.clone(),
+            trail: Vec::new(),
+        }
+    }
+
+    //Forgets the changes made so far, they can no longer be undone
+    pub(crate) fn clear_trail(&mut self) {
+        self.trail.clear();
+    }
+
+    pub(crate) fn row(&self, i: usize) -> Vec<Status> {
+        (0..self.width).map(|j| self.get(i, j)).collect()
+    }
+
+    pub(crate) fn column(&self, j: usize) -> Vec<Status> {
+        (0..self.height).map(|i| self.get(i, j)).collect()
+    }
+
+    pub(crate) fn rows(&self) -> Vec<Vec<Status>> {
+        (0..self.height).map(|i| self.row(i)).collect()
+    }
+
+    pub(crate) fn unknown_count(&self) -> usize {
+        let known: u32 = self.known.iter().map(|w| w.count_ones()).sum();
+        self.height * self.width - known as usize
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        self.unknown_count() == 0
+    }
+
+    //The cells unknown here that are known, and alike, in both a and b
+    pub(crate) fn agreeing_cells(&self, a: &Grid, b: &Grid) -> Vec<(usize, usize, Status)> {
+        let mut cells = Vec::new();
+        for w in 0..self.known.len() {
+            let mut agree = a.known[w] & b.known[w] & !(a.marked[w] ^ b.marked[w]) & !self.known[w];
+            while agree != 0 {
+                let bit = agree.trailing_zeros() as usize;
+                agree &= agree - 1;
+                let (i, j) = (w / self.words, (w % self.words) * BITS + bit);
+                cells.push((i, j, a.get(i, j)));
+            }
+        }
+        cells
+    }
+}
+
+#[test]
+fn cells_are_packed() {
+    let mut grid = Grid::new(3, 70);
+    assert_eq!(grid.get(2, 69), Status::Unknown);
+    grid.set(2, 69, Status::Marked);
+    grid.set(2, 68, Status::Empty);
+    grid.set(0, 0, Status::Marked);
+    assert_eq!(grid.get(2, 69), Status::Marked);
+    assert_eq!(grid.get(2, 68), Status::Empty);
+    assert_eq!(grid.get(1, 69), Status::Unknown);
+    assert_eq!(grid.unknown_count(), 3 * 70 - 3);
+    assert_eq!(Grid::filled(2, 2, Status::Empty).unknown_count(), 0);
+}
+
+#[test]
+fn changes_are_undone() {
+    let mut grid = Grid::new(2, 2);
+    grid.set(0, 0, Status::Marked);
+    let checkpoint = grid.checkpoint();
+    grid.set(0, 0, Status::Empty);
+    grid.set(1, 1, Status::Marked);
+    grid.undo(checkpoint);
+    assert_eq!(
+        grid.rows(),
+        vec![
+            vec![Status::Marked, Status::Unknown],
+            vec![Status::Unknown, Status::Unknown]
+        ]
+    );
+    grid.undo(0);
+    assert_eq!(grid.unknown_count(), 4);
+}
+
+#[test]
+fn agreement_between_grids() {
+    let here = Grid::new(1, 3);
+    let mut a = here.clone();
+    let mut b = here.clone();
+    a.set(0, 0, Status::Marked);
+    b.set(0, 0, Status::Marked);
+    a.set(0, 1, Status::Marked);
+    b.set(0, 1, Status::Empty);
+    a.set(0, 2, Status::Empty);
+    assert_eq!(here.agreeing_cells(&a, &b), vec![(0, 0, Status::Marked)]);
+}
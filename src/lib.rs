@@ -0,0 +1,508 @@
+//! A Paint By Numbers (nonogram) solver.
+//!
+//! A [`Puzzle`] is built from its row and column clues, either directly or by
+//! reading a puzzle file with [`read_nonogram`], and [`Puzzle::solve`] returns
+//! the filled in grid as a [`Solution`].
+mod bitmap;
+mod board;
+mod color;
+mod generate;
+mod grid;
+mod line;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod parse;
+mod probe;
+mod propagate;
+mod rate;
+#[cfg(feature = "sat")]
+mod sat;
+mod schema;
+mod solver;
+mod svg;
+mod trace;
+mod unique;
+mod validate;
+mod webpbn;
+
+use board::Board;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+pub use bitmap::{parse_bitmap, read_bitmap, write_pbm, write_pgm, write_png};
+pub use board::Status;
+pub use color::{Color, ColorClue, ColorPuzzle, ColorSolution};
+pub use generate::{generate_puzzle, GenerateOptions, GeneratedPuzzle};
+pub use line::solve_line;
+pub use parse::{
+    is_color_nonogram, is_non, parse_color_nonogram, parse_non, parse_nonogram,
+    read_color_nonogram, read_non, read_nonogram, NonPuzzle, ParseError,
+};
+pub use rate::{rate_puzzle, Difficulty, Rating};
+pub use schema::{is_json, is_toml, read_document, Given, PuzzleDocument, SolveReport, Verdict};
+pub use solver::{
+    Branching, Engine, FixedCells, Level, Limits, SolveOptions, SolveOutcome, SolveStats,
+};
+pub use svg::{render_svg, SvgOptions};
+pub use trace::{Rule, SolveTrace, Step};
+pub use unique::{add_givens, flip_cells, Edit, Edited};
+pub use validate::{Line, ValidationError};
+pub use webpbn::{is_webpbn, parse_webpbn, read_webpbn, WebpbnPuzzle};
+
+///The clues of a nonogram, ready to be solved. Serializes as a [`PuzzleDocument`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "PuzzleDocument", try_from = "PuzzleDocument")]
+pub struct Puzzle {
+    board: Board,
+}
+
+///A completely filled in grid that satisfies every clue of a puzzle, or the partly
+///filled in grid of a [`SolveOutcome::Timeout`]. Serializes as its rows, see
+///[`PuzzleDocument::solution`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(into = "Vec<String>")]
+pub struct Solution {
+    board: Board,
+}
+
+impl Puzzle {
+    ///Creates a puzzle from the clues of each row (top to bottom) and each column (left to right).
+    ///An empty clue line, or a line holding a single 0, describes a row/column with no marked cells.
+    pub fn new(rows: Vec<Vec<usize>>, columns: Vec<Vec<usize>>) -> Puzzle {
+        Puzzle {
+            board: Board::new(&rows, &columns),
+        }
+    }
+
+    ///Creates the puzzle whose clues describe a picture, given as rows of cells top to bottom
+    ///with true for the marked ones, e.g. an image read with [`read_bitmap`]. Rows shorter than
+    ///the first one are padded with empty cells.
+    pub fn from_picture(marked: &[Vec<bool>]) -> Puzzle {
+        let width = marked.first().map_or(0, Vec::len);
+        let cell = |i: usize, j: usize| marked[i].get(j).copied().unwrap_or(false);
+        let rows = (0..marked.len())
+            .map(|i| runs((0..width).map(|j| cell(i, j))))
+            .collect();
+        let columns = (0..width)
+            .map(|j| runs((0..marked.len()).map(|i| cell(i, j))))
+            .collect();
+        Puzzle::new(rows, columns)
+    }
+
+    ///Writes the clues in the format of [`parse_nonogram`], which has no room for givens
+    pub fn to_nonogram(&self) -> String {
+        let mut text = format!("{},{}\n", self.width(), self.height());
+        for clues in self.column_clues().iter().chain(self.row_clues().iter()) {
+            let clues: Vec<String> = clues.iter().map(|clue| clue.to_string()).collect();
+            text.push_str(&clues.join(","));
+            text.push('\n');
+        }
+        text
+    }
+
+    ///Reads a puzzle from a file, see [`read_nonogram`]
+    pub fn from_file<P: AsRef<Path>>(file: P) -> Result<Puzzle, ParseError> {
+        read_nonogram(file)
+    }
+
+    ///Number of columns in the grid
+    pub fn width(&self) -> usize {
+        self.board.size[1]
+    }
+
+    ///Number of rows in the grid
+    pub fn height(&self) -> usize {
+        self.board.size[0]
+    }
+
+    ///The clues of each row, top to bottom
+    pub fn row_clues(&self) -> Vec<Vec<usize>> {
+        self.board
+            .v_keys
+            .iter()
+            .map(|keys| keys.iter().map(|key| key.value).collect())
+            .collect()
+    }
+
+    ///The clues of each column, left to right
+    pub fn column_clues(&self) -> Vec<Vec<usize>> {
+        self.board
+            .h_keys
+            .iter()
+            .map(|keys| keys.iter().map(|key| key.value).collect())
+            .collect()
+    }
+
+    ///Fixes the state of a cell before solving, like the cells some puzzles print already filled
+    ///in. Setting a cell back to [`Status::Unknown`] removes its given. Panics if the cell is
+    ///outside the grid.
+    pub fn set_given(&mut self, row: usize, column: usize, state: Status) {
+        assert!(row < self.height() && column < self.width());
+        self.board.grid.set(row, column, state);
+        self.board.grid.clear_trail();
+    }
+
+    ///The cells fixed with [`Puzzle::set_given`], as (row, column, state) in row-major order
+    pub fn givens(&self) -> Vec<(usize, usize, Status)> {
+        let mut givens = Vec::new();
+        for (i, row) in self.board.grid.rows().into_iter().enumerate() {
+            for (j, state) in row.into_iter().enumerate() {
+                if state != Status::Unknown {
+                    givens.push((i, j, state));
+                }
+            }
+        }
+        givens
+    }
+
+    ///Returns the largest number of clues in any row and in any column, as (rows, columns)
+    pub fn key_dimensions(&self) -> (usize, usize) {
+        let mut vkey_max = 0;
+        let mut hkey_max = 0;
+        self.board.get_key_dimensions(&mut vkey_max, &mut hkey_max);
+        (vkey_max, hkey_max)
+    }
+
+    ///Checks that the clues are consistent with each other and with the size of the grid,
+    ///returning every problem found. A mismatch between the dimension line of a puzzle file
+    ///and its number of clue lines is already rejected by [`read_nonogram`].
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        validate::validate(self)
+    }
+
+    ///Solves the puzzle, returning None if no grid satisfies the clues
+    pub fn solve(&self) -> Option<Solution> {
+        self.solve_with(&SolveOptions::default()).0
+    }
+
+    ///Solves the puzzle with the given settings, also returning counters collected while solving.
+    ///Returns None if the search is stopped by `options.limits`, see [`SolveStats::interrupted`].
+    pub fn solve_with(&self, options: &SolveOptions) -> (Option<Solution>, SolveStats) {
+        let (outcome, stats) = self.solve_outcome(options);
+        match outcome {
+            SolveOutcome::Solved(solution) => (Some(solution), stats),
+            _ => (None, stats),
+        }
+    }
+
+    ///Solves the puzzle, giving up once one of the limits is reached. A puzzle whose clues
+    ///fail [`Puzzle::validate`] is reported as [`SolveOutcome::Unsat`].
+    pub fn solve_with_limits(&self, limits: Limits) -> SolveOutcome {
+        let options = SolveOptions {
+            limits,
+            ..Default::default()
+        };
+        self.solve_outcome(&options).0
+    }
+
+    ///Like [`Puzzle::solve_with`], but tells an unsolvable puzzle apart from one that ran out of time
+    pub fn solve_outcome(&self, options: &SolveOptions) -> (SolveOutcome, SolveStats) {
+        let mut stats = SolveStats::default();
+        if self.validate().is_err() {
+            return (SolveOutcome::Unsat, stats);
+        }
+        let mut search = solver::find_solution(self.board.clone(), options, &mut stats);
+        let outcome = match (search.solutions.pop(), search.partial) {
+            (Some(board), _) => SolveOutcome::Solved(Solution { board }),
+            (None, Some(board)) => SolveOutcome::Timeout(Solution { board }),
+            (None, None) => SolveOutcome::Unsat,
+        };
+        (outcome, stats)
+    }
+
+    ///Solves the puzzle, also returning a log of every cell fixed along the way, with the
+    ///rule and row/column that forced it, and of the guesses the search had to make
+    pub fn solve_traced(&self) -> (Option<Solution>, SolveTrace) {
+        let mut trace = SolveTrace::new();
+        if self.validate().is_err() {
+            return (None, trace);
+        }
+        let solution = solver::find_solutions_traced(
+            self.board.clone(),
+            &SolveOptions::default(),
+            &mut SolveStats::default(),
+            &mut trace,
+            1,
+        )
+        .solutions
+        .pop()
+        .map(|board| Solution { board });
+        (solution, trace)
+    }
+
+    ///Returns up to limit distinct solutions of the puzzle
+    pub fn solutions(&self, limit: usize) -> Vec<Solution> {
+        self.solutions_with(&SolveOptions::default(), limit).0
+    }
+
+    ///Returns up to limit distinct solutions with the given settings, along with the counters
+    ///collected while searching for them. If `options.limits` stop the search early, the
+    ///solutions found so far are returned.
+    pub fn solutions_with(
+        &self,
+        options: &SolveOptions,
+        limit: usize,
+    ) -> (Vec<Solution>, SolveStats) {
+        let (solutions, _, stats) = self.solutions_outcome(options, limit);
+        (solutions, stats)
+    }
+
+    ///Like [`Puzzle::solutions_with`], but when `options.limits` stop the search early also
+    ///returns the cells known by then, as in [`SolveOutcome::Timeout`]. The partial solution
+    ///is None when the search ran to the end.
+    pub fn solutions_outcome(
+        &self,
+        options: &SolveOptions,
+        limit: usize,
+    ) -> (Vec<Solution>, Option<Solution>, SolveStats) {
+        let mut stats = SolveStats::default();
+        if self.validate().is_err() {
+            return (Vec::new(), None, stats);
+        }
+        let search = solver::find_solutions(self.board.clone(), options, &mut stats, limit);
+        let solutions = search
+            .solutions
+            .into_iter()
+            .map(|board| Solution { board })
+            .collect();
+        let partial = search.partial.map(|board| Solution { board });
+        (solutions, partial, stats)
+    }
+
+    ///Counts the solutions of the puzzle, stopping once limit solutions are found
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions(limit).len()
+    }
+
+    ///Returns true if the puzzle has exactly one solution
+    pub fn is_unique(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+}
+
+//The lengths of the blocks of marked cells in a line, its clues
+fn runs<I: IntoIterator<Item = bool>>(cells: I) -> Vec<usize> {
+    let mut runs = Vec::new();
+    let mut length = 0;
+    for marked in cells {
+        if marked {
+            length += 1;
+        } else if length > 0 {
+            runs.push(length);
+            length = 0;
+        }
+    }
+    if length > 0 {
+        runs.push(length);
+    }
+    runs
+}
+
+impl Solution {
+    //A solution holding the given picture, e.g. the goal stored in a puzzle file
+    pub(crate) fn from_picture(puzzle: &Puzzle, marked: &[Vec<bool>]) -> Solution {
+        let mut board = puzzle.board.snapshot();
+        for (i, row) in marked.iter().enumerate() {
+            for (j, &marked) in row.iter().enumerate() {
+                let state = if marked {
+                    Status::Marked
+                } else {
+                    Status::Empty
+                };
+                board.grid.set(i, j, state);
+            }
+        }
+        board.grid.clear_trail();
+        Solution { board }
+    }
+
+    ///Number of columns in the grid
+    pub fn width(&self) -> usize {
+        self.board.size[1]
+    }
+
+    ///Number of rows in the grid
+    pub fn height(&self) -> usize {
+        self.board.size[0]
+    }
+
+    ///State of the cell at the given row and column
+    pub fn cell(&self, row: usize, column: usize) -> Status {
+        self.board.grid.get(row, column)
+    }
+
+    ///Returns true if the cell at the given row and column is marked
+    pub fn is_marked(&self, row: usize, column: usize) -> bool {
+        self.cell(row, column) == Status::Marked
+    }
+
+    ///Returns the (row, column) of every cell that differs between the two solutions
+    pub fn differences(&self, other: &Solution) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for i in 0..self.height().min(other.height()) {
+            for j in 0..self.width().min(other.width()) {
+                if self.cell(i, j) != other.cell(i, j) {
+                    cells.push((i, j));
+                }
+            }
+        }
+        cells
+    }
+
+    ///The grid as rows of cell states, top to bottom
+    pub fn rows(&self) -> Vec<Vec<Status>> {
+        self.board.grid.rows()
+    }
+}
+
+///Writes the grid one row per line, with '#' for marked cells, '.' for empty cells and '?' for unknown cells
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for state in row {
+                let c = match state {
+                    Status::Marked => '#',
+                    Status::Empty => '.',
+                    Status::Unknown => '?',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn solve_small_puzzle() {
+    // .X.
+    // XXX
+    // .X.
+    let p = Puzzle::new(
+        vec![vec![1], vec![3], vec![1]],
+        vec![vec![1], vec![3], vec![1]],
+    );
+    let s = p.solve().expect("puzzle should be solvable");
+    assert_eq!(s.width(), 3);
+    assert_eq!(s.height(), 3);
+    assert!(s.is_marked(1, 0));
+    assert!(s.is_marked(0, 1));
+    assert!(!s.is_marked(0, 0));
+    assert_eq!(s.cell(2, 2), Status::Empty);
+    assert_eq!(s.to_string(), ".#.\n###\n.#.\n");
+}
+
+#[test]
+fn puzzle_from_picture() {
+    // ##.
+    // ...
+    // #.#
+    let picture = vec![
+        vec![true, true, false],
+        vec![false, false, false],
+        vec![true, false, true],
+    ];
+    let p = Puzzle::from_picture(&picture);
+    assert_eq!(p.row_clues(), vec![vec![2], vec![0], vec![1, 1]]);
+    assert_eq!(p.column_clues(), vec![vec![1, 1], vec![1], vec![1]]);
+    let text = p.to_nonogram();
+    assert_eq!(text, "3,3\n1,1\n1\n1\n2\n0\n1,1\n");
+    let read = parse_nonogram(&text).unwrap();
+    assert_eq!(read.solve().unwrap().to_string(), "##.\n...\n#.#\n");
+}
+
+#[test]
+fn unsolvable_puzzle() {
+    let p = Puzzle::new(vec![vec![2], vec![0]], vec![vec![0], vec![1]]);
+    assert!(p.solve().is_none());
+}
+
+#[test]
+fn invalid_puzzle_is_not_solved() {
+    let p = Puzzle::new(vec![vec![3]], vec![vec![1], vec![1]]);
+    assert!(p.validate().is_err());
+    assert!(p.solve().is_none());
+}
+
+#[test]
+fn count_and_compare_solutions() {
+    let p = Puzzle::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    assert_eq!(p.count_solutions(10), 2);
+    assert_eq!(p.count_solutions(1), 1);
+    assert!(!p.is_unique());
+    let solutions = p.solutions(2);
+    assert_eq!(
+        solutions[0].differences(&solutions[1]),
+        vec![(0, 0), (0, 1), (1, 0), (1, 1)]
+    );
+    assert!(read_nonogram("./src/puzzles/nono3.txt")
+        .unwrap()
+        .is_unique());
+}
+
+#[test]
+fn solve_within_limits() {
+    let p = read_nonogram("./src/puzzles/nono3.txt").unwrap();
+    assert!(matches!(
+        p.solve_with_limits(Limits::default()),
+        SolveOutcome::Solved(_)
+    ));
+    let unsat = Puzzle::new(vec![vec![2], vec![0]], vec![vec![0], vec![1]]);
+    assert!(matches!(
+        unsat.solve_with_limits(Limits::default()),
+        SolveOutcome::Unsat
+    ));
+
+    //the empty column is certain, the rest needs a guess
+    let p = Puzzle::new(vec![vec![1], vec![1]], vec![vec![1], vec![1], vec![0]]);
+    let limits = Limits {
+        max_nodes: Some(0),
+        ..Default::default()
+    };
+    match p.solve_with_limits(limits.clone()) {
+        SolveOutcome::Timeout(partial) => assert_eq!(partial.to_string(), "??.\n??.\n"),
+        outcome => panic!("expected a timeout, got {:?}", outcome),
+    }
+    let options = SolveOptions {
+        limits,
+        ..Default::default()
+    };
+    let (solutions, partial, stats) = p.solutions_outcome(&options, 2);
+    assert!(solutions.is_empty() && stats.interrupted);
+    assert_eq!(partial.unwrap().to_string(), "??.\n??.\n");
+    let (solutions, partial, _) = p.solutions_outcome(&SolveOptions::default(), 2);
+    assert_eq!(solutions.len(), 2);
+    assert!(partial.is_none());
+}
+
+#[test]
+fn trace_covers_every_cell() {
+    let p = read_nonogram("./src/puzzles/nono3.txt").unwrap();
+    let (solution, trace) = p.solve_traced();
+    assert!(solution.is_some());
+    assert_eq!(trace.guesses(), 0);
+    let fixed: usize = trace
+        .steps()
+        .iter()
+        .map(|step| match step {
+            Step::Deduction { cells, .. } => cells.len(),
+            _ => 0,
+        })
+        .sum();
+    assert_eq!(fixed, 100);
+
+    let p = Puzzle::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let (_, trace) = p.solve_traced();
+    assert!(trace.steps()[0].is_guess());
+    assert!(trace.steps()[1..].iter().all(|step| step.depth() == 1));
+}
+
+#[test]
+fn read_puzzle_file() {
+    let p = read_nonogram("./src/puzzles/nono3.txt").unwrap();
+    assert_eq!(p.width(), 10);
+    assert_eq!(p.height(), 10);
+    assert_eq!(p.column_clues()[0], vec![4]);
+    assert!(p.solve().is_some());
+}
@@ -0,0 +1,351 @@
+use crate::board::Status;
+
+///Solves a single row or column exactly.
+///
+///Given the clues of the line and the current state of its cells, returns the
+///line with every cell that has the same value in all placements of the clues
+///consistent with the known cells filled in. Returns None if no placement is
+///consistent, i.e. the line contradicts its clues. A clue list that is empty
+///or holds a single 0 describes an empty line.
+pub fn solve_line(clues: &[usize], cells: &[Status]) -> Option<Vec<Status>> {
+    let clues: Vec<usize> = clues.iter().copied().filter(|&c| c > 0).collect();
+    let n = cells.len();
+    let k = clues.len();
+    let fwd = prefix_fits(&clues, cells);
+    if !fwd[k][n] {
+        return None;
+    }
+    let bwd = suffix_fits(&clues, cells);
+
+    //cell p can be empty if the first j blocks fit before it and the rest fit after it
+    let mut can_be_empty = vec![false; n];
+    for (p, empty) in can_be_empty.iter_mut().enumerate() {
+        if cells[p] != Status::Marked {
+            *empty = (0..=k).any(|j| fwd[j][p] && bwd[j][p + 1]);
+        }
+    }
+
+    //every valid position of every block adds to the cells it may cover
+    let mut cover = vec![0i32; n + 1];
+    for (j, &len) in clues.iter().enumerate() {
+        if len > n {
+            return None;
+        }
+        for start in 0..=(n - len) {
+            if placed(cells, &fwd, &bwd, j, start, len) {
+                cover[start] += 1;
+                cover[start + len] -= 1;
+            }
+        }
+    }
+
+    let mut result = cells.to_vec();
+    let mut covered = 0;
+    for p in 0..n {
+        covered += cover[p];
+        let can_be_marked = covered > 0;
+        result[p] = match (can_be_marked, can_be_empty[p]) {
+            (true, false) => Status::Marked,
+            (false, true) => Status::Empty,
+            (true, true) => Status::Unknown,
+            (false, false) => return None,
+        };
+    }
+    Some(result)
+}
+
+//Returns true if block j of len cells can start at start, with the blocks before it fitting
+//in the cells before it and the blocks after it in the cells after it
+fn placed(
+    cells: &[Status],
+    fwd: &[Vec<bool>],
+    bwd: &[Vec<bool>],
+    j: usize,
+    start: usize,
+    len: usize,
+) -> bool {
+    let (n, k) = (cells.len(), fwd.len() - 1);
+    if !fits_block(cells, start, len) {
+        return false;
+    }
+    let before = if j == 0 {
+        fwd[0][start]
+    } else {
+        start > 0 && cells[start - 1] != Status::Marked && fwd[j][start - 1]
+    };
+    let end = start + len;
+    let after = if j == k - 1 {
+        bwd[k][end]
+    } else {
+        end < n && cells[end] != Status::Marked && bwd[j + 1][end + 1]
+    };
+    before && after
+}
+
+///Returns, for every non-zero clue of the line, the first and last cell it can cover in the
+///placements consistent with the known cells. Returns None if no placement is consistent.
+pub(crate) fn clue_spans(clues: &[usize], cells: &[Status]) -> Option<Vec<(usize, usize)>> {
+    let clues: Vec<usize> = clues.iter().copied().filter(|&c| c > 0).collect();
+    let n = cells.len();
+    let fwd = prefix_fits(&clues, cells);
+    if !fwd[clues.len()][n] || clues.iter().any(|&len| len > n) {
+        return None;
+    }
+    let bwd = suffix_fits(&clues, cells);
+    let spans = clues.iter().enumerate().map(|(j, &len)| {
+        let mut starts = (0..=(n - len)).filter(|&start| placed(cells, &fwd, &bwd, j, start, len));
+        let first = starts.next()?;
+        let last = starts.next_back().unwrap_or(first);
+        Some((first, last + len - 1))
+    });
+    spans.collect()
+}
+
+///Fixes only the cells a single clue settles: those covered by every placement of one clue,
+///and those no clue can reach. Weaker than [`solve_line`], it is used to grade puzzles.
+pub(crate) fn overlap_line(clues: &[usize], cells: &[Status]) -> Option<Vec<Status>> {
+    let spans = clue_spans(clues, cells)?;
+    let lengths = clues.iter().copied().filter(|&c| c > 0);
+    let mut result = cells.to_vec();
+    for ((first, last), len) in spans.iter().copied().zip(lengths) {
+        //the clue starts no later than last + 1 - len and ends no earlier than first + len
+        for cell in result.iter_mut().take(first + len).skip(last + 1 - len) {
+            *cell = Status::Marked;
+        }
+    }
+    for (p, cell) in result.iter_mut().enumerate() {
+        if *cell == Status::Unknown && spans.iter().all(|&(first, last)| p < first || p > last) {
+            *cell = Status::Empty;
+        }
+    }
+    Some(result)
+}
+
+//Returns true if a block of len cells can start at start without covering an empty cell
+fn fits_block(cells: &[Status], start: usize, len: usize) -> bool {
+    cells[start..start + len]
+        .iter()
+        .all(|&c| c != Status::Empty)
+}
+
+//fits[j][i] is true if the first j blocks can be placed in the first i cells,
+//with the cells not covered by a block left empty
+fn prefix_fits(clues: &[usize], cells: &[Status]) -> Vec<Vec<bool>> {
+    let n = cells.len();
+    let k = clues.len();
+    let mut fits = vec![vec![false; n + 1]; k + 1];
+    fits[0][0] = true;
+    for i in 1..=n {
+        fits[0][i] = fits[0][i - 1] && cells[i - 1] != Status::Marked;
+    }
+    for j in 1..=k {
+        let len = clues[j - 1];
+        for i in 1..=n {
+            let mut fit = fits[j][i - 1] && cells[i - 1] != Status::Marked;
+            if !fit && i >= len && fits_block(cells, i - len, len) {
+                let start = i - len;
+                fit = if j == 1 {
+                    fits[0][start]
+                } else {
+                    start > 0 && cells[start - 1] != Status::Marked && fits[j - 1][start - 1]
+                };
+            }
+            fits[j][i] = fit;
+        }
+    }
+    fits
+}
+
+//fits[j][i] is true if the blocks from j onwards can be placed in the cells from i onwards
+fn suffix_fits(clues: &[usize], cells: &[Status]) -> Vec<Vec<bool>> {
+    let reversed_clues: Vec<usize> = clues.iter().rev().copied().collect();
+    let reversed_cells: Vec<Status> = cells.iter().rev().copied().collect();
+    let reversed = prefix_fits(&reversed_clues, &reversed_cells);
+    let n = cells.len();
+    let k = clues.len();
+    let mut fits = vec![vec![false; n + 1]; k + 1];
+    for j in 0..=k {
+        for i in 0..=n {
+            fits[j][i] = reversed[k - j][n - i];
+        }
+    }
+    fits
+}
+
+///Returns, for every cell of the line, the fraction of the placements of the clues
+///consistent with the known cells that mark it. Returns None if no placement is consistent.
+pub(crate) fn marked_probability(clues: &[usize], cells: &[Status]) -> Option<Vec<f64>> {
+    let clues: Vec<usize> = clues.iter().copied().filter(|&c| c > 0).collect();
+    let n = cells.len();
+    let k = clues.len();
+    let fwd = prefix_counts(&clues, cells);
+    let total = fwd[k][n];
+    if total == 0.0 {
+        return None;
+    }
+    let reversed_clues: Vec<usize> = clues.iter().rev().copied().collect();
+    let reversed_cells: Vec<Status> = cells.iter().rev().copied().collect();
+    let bwd = prefix_counts(&reversed_clues, &reversed_cells);
+    //number of ways the blocks from j onwards fit in the cells from i onwards
+    let after = |j: usize, i: usize| bwd[k - j][n - i];
+
+    let mut cover = vec![0.0; n + 1];
+    for (j, &len) in clues.iter().enumerate() {
+        if len > n {
+            return None;
+        }
+        for start in 0..=(n - len) {
+            if !fits_block(cells, start, len) {
+                continue;
+            }
+            let ways_before = if j == 0 {
+                fwd[0][start]
+            } else if start > 0 && cells[start - 1] != Status::Marked {
+                fwd[j][start - 1]
+            } else {
+                0.0
+            };
+            let end = start + len;
+            let ways_after = if j == k - 1 {
+                after(k, end)
+            } else if end < n && cells[end] != Status::Marked {
+                after(j + 1, end + 1)
+            } else {
+                0.0
+            };
+            let ways = ways_before * ways_after;
+            cover[start] += ways;
+            cover[end] -= ways;
+        }
+    }
+
+    let mut covered = 0.0;
+    Some(
+        (0..n)
+            .map(|p| {
+                covered += cover[p];
+                (covered / total).clamp(0.0, 1.0)
+            })
+            .collect(),
+    )
+}
+
+//counts[j][i] is the number of ways the first j blocks can be placed in the first i cells
+fn prefix_counts(clues: &[usize], cells: &[Status]) -> Vec<Vec<f64>> {
+    let n = cells.len();
+    let k = clues.len();
+    let mut counts = vec![vec![0.0; n + 1]; k + 1];
+    counts[0][0] = 1.0;
+    for i in 1..=n {
+        if cells[i - 1] != Status::Marked {
+            counts[0][i] = counts[0][i - 1];
+        }
+    }
+    for j in 1..=k {
+        let len = clues[j - 1];
+        for i in 1..=n {
+            let mut count = 0.0;
+            if cells[i - 1] != Status::Marked {
+                count += counts[j][i - 1];
+            }
+            if i >= len && fits_block(cells, i - len, len) {
+                let start = i - len;
+                if j == 1 {
+                    count += counts[0][start];
+                } else if start > 0 && cells[start - 1] != Status::Marked {
+                    count += counts[j - 1][start - 1];
+                }
+            }
+            counts[j][i] = count;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+fn line(s: &str) -> Vec<Status> {
+    s.chars()
+        .map(|c| match c {
+            '#' => Status::Marked,
+            '.' => Status::Empty,
+            _ => Status::Unknown,
+        })
+        .collect()
+}
+
+#[test]
+fn overlap_marks_middle_cells() {
+    assert_eq!(
+        solve_line(&[6], &line("??????????")),
+        Some(line("????##????"))
+    );
+    assert_eq!(
+        solve_line(&[3, 4], &line("??????????")),
+        Some(line("??#???##??"))
+    );
+    assert_eq!(
+        solve_line(&[4, 5], &line("??????????")),
+        Some(line("####.#####"))
+    );
+}
+
+#[test]
+fn full_and_empty_lines() {
+    assert_eq!(solve_line(&[2, 1], &line("????")), Some(line("##.#")));
+    assert_eq!(solve_line(&[0], &line("???")), Some(line("...")));
+    assert_eq!(solve_line(&[], &line("???")), Some(line("...")));
+}
+
+#[test]
+fn known_cells_force_more() {
+    //any block of 3 covering the marked cell stays clear of both ends
+    assert_eq!(solve_line(&[3], &line("???#???")), Some(line(".??#??.")));
+    assert_eq!(solve_line(&[1, 1], &line("?#??.")), Some(line(".#.#.")));
+    assert_eq!(solve_line(&[2], &line("?.???")), Some(line("..?#?")));
+}
+
+#[test]
+fn contradictions() {
+    assert_eq!(solve_line(&[2], &line("#.#")), None);
+    assert_eq!(solve_line(&[3], &line("??.??")), None);
+    assert_eq!(solve_line(&[0], &line("?#?")), None);
+    assert_eq!(solve_line(&[1, 1], &line("##??")), None);
+}
+
+#[test]
+fn placement_probabilities() {
+    //a 1 in three cells is equally likely to be anywhere
+    let p = marked_probability(&[1], &line("???")).unwrap();
+    assert!(p.iter().all(|&x| (x - 1.0 / 3.0).abs() < 1e-9));
+    //[2] in four cells: placements ##.. .##. ..##
+    let p = marked_probability(&[2], &line("????")).unwrap();
+    let expected = [1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0, 1.0 / 3.0];
+    assert!(p
+        .iter()
+        .zip(expected.iter())
+        .all(|(a, b)| (a - b).abs() < 1e-9));
+    assert_eq!(marked_probability(&[1, 1], &line("?#")), None);
+}
+
+#[test]
+fn overlap_only() {
+    assert_eq!(
+        overlap_line(&[6], &line("??????????")),
+        Some(line("????##????"))
+    );
+    assert_eq!(overlap_line(&[3], &line("??#???")), Some(line("??#??.")));
+    //either clue may cover the marked cell, only the line solver sees that its neighbours are empty
+    assert_eq!(overlap_line(&[1, 1], &line("??#??")), Some(line("??#??")));
+    assert_eq!(solve_line(&[1, 1], &line("??#??")), Some(line("?.#.?")));
+}
+
+#[test]
+fn spans_of_clues() {
+    assert_eq!(
+        clue_spans(&[3, 1], &line("??????")),
+        Some(vec![(0, 3), (4, 5)])
+    );
+    assert_eq!(clue_spans(&[2], &line("?.??#")), Some(vec![(3, 4)]));
+    assert_eq!(clue_spans(&[0], &line("??")), Some(vec![]));
+    assert_eq!(clue_spans(&[3], &line("??.??")), None);
+}
@@ -1,827 +1,1004 @@
 extern crate termion;
+mod cli;
+
+use cli::{Command, Fix, Format, GenerateArgs, ImageOptions, Options, Outcome, StatsFormat};
+use nonogram_solver::{
+    add_givens, flip_cells, generate_puzzle, is_color_nonogram, is_webpbn, rate_puzzle,
+    read_bitmap, read_color_nonogram, read_nonogram, read_webpbn, render_svg, write_pbm, write_pgm,
+    write_png, Branching, ColorPuzzle, ColorSolution, Edited, Engine, GenerateOptions, Level,
+    Limits, ParseError, Puzzle, PuzzleDocument, Solution, SolveOptions, SolveOutcome, SolveReport,
+    SolveStats, Status, SvgOptions, Verdict,
+};
+use serde::Serialize;
 use std::cmp;
 use std::convert::{TryFrom, TryInto};
-use std::fs::File;
-use std::io::{self, stdout, BufRead, BufReader, Write};
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Instant, SystemTime};
 use termion::color;
 use termion::raw::IntoRawMode;
-#[derive(Debug, Copy, Clone, PartialEq)]
-pub enum Status {
-    Empty,
-    Marked,
-    Unknown,
-}
-impl Default for Status {
-    fn default() -> Self {
-        Status::Unknown
-    }
-}
-#[derive(Debug, Default, Copy, Clone)]
-struct Point {
-    cell_state: Status,
-}
-
-#[derive(Debug, Default, Copy, Clone)]
-struct NonoKey {
-    value: usize,
-    upper_bound: usize,
-    lower_bound: usize,
-}
-#[derive(Default, Clone)]
-struct Board {
-    grid: Vec<Vec<Point>>,
-    h_keys: Vec<Vec<NonoKey>>,
-    v_keys: Vec<Vec<NonoKey>>,
-    size: Vec<usize>,
-}
 
 const CELL: &str = "▉▉▉▉▉";
 const UNKNOWN: &str = "?????";
+const COLOR_CELL: &str = "▉▉";
+const PUZZLE_COUNT: usize = 8;
 
 fn main() {
-    let mut b: Board = Default::default();
-    let mut vkey_max: usize = 0;
-    let mut hkey_max: usize = 0;
-    let file: String = user_puzzle_choice();
+    let command = match cli::parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(Outcome::Invalid.exit_code());
+        }
+    };
+    match command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::Interactive => interactive(),
+        Command::Solve(options) => process::exit(solve_files(&options).exit_code()),
+        Command::Compare(files) => process::exit(compare_files(&files).exit_code()),
+        Command::Explain(file) => process::exit(explain_file(&file).exit_code()),
+        Command::Rate(files) => process::exit(rate_files(&files).exit_code()),
+        Command::Image(options) => process::exit(convert_image(&options).exit_code()),
+        Command::Generate(args) => process::exit(generate_puzzles(&args).exit_code()),
+    }
+}
+
+//lets the user pick one of the bundled puzzles and draws its solution in the terminal
+fn interactive() {
+    let file: String = match user_puzzle_choice() {
+        Some(file) => file,
+        None => {
+            println!("Invalid input");
+            return;
+        }
+    };
     println!("Working...");
 
-    b.read_nonogram(file).expect("Could not read file");
+    let puzzle = match read_nonogram(&file) {
+        Ok(puzzle) => puzzle,
+        Err(e) => {
+            report_parse_error(&file, &e);
+            return;
+        }
+    };
 
-    if !init(&b) {
+    if let Err(errors) = puzzle.validate() {
+        for e in errors.iter() {
+            println!("{}: {}", file, e);
+        }
+        return;
+    }
+
+    if !init(&puzzle) {
         println!("The given board is too large, try a smaller size.");
         return;
     }
 
-    let stdout = stdout().into_raw_mode().unwrap();
+    let mut stdout = stdout().into_raw_mode().unwrap();
 
-    b.get_key_dimensions(&mut vkey_max, &mut hkey_max);
+    let (vkey_max, hkey_max) = puzzle.key_dimensions();
 
-    if !find_solution(b, vkey_max, hkey_max, stdout) {
-        println!("Could not find solution");
+    match puzzle.solve() {
+        Some(solution) => update_board(&puzzle, &solution, &[], &mut stdout, vkey_max, hkey_max),
+        None => println!("Could not find solution"),
     }
 }
 //user chooses the nonogram they want to solve
-fn user_puzzle_choice() -> String {
-    println!("Choose a puzzle (1-7): ");
+fn user_puzzle_choice() -> Option<String> {
+    println!("Choose a puzzle (1-{}): ", PUZZLE_COUNT);
     let mut input = String::new();
     io::stdin()
         .read_line(&mut input)
         .expect("Failed to parse input.");
-    match input.trim().parse::<usize>().unwrap() {
-        1 => "./src/puzzles/nono1.txt".to_string(),
-        2 => "./src/puzzles/nono2.txt".to_string(),
-        3 => "./src/puzzles/nono3.txt".to_string(),
-        4 => "./src/puzzles/nono4.txt".to_string(),
-        5 => "./src/puzzles/nono5.txt".to_string(),
-        6 => "./src/puzzles/nono6.txt".to_string(),
-        7 => "./src/puzzles/nono7.txt".to_string(),
-        _ => panic!("Invalid input"),
+    match input.trim().parse::<usize>() {
+        Ok(n) if (1..=PUZZLE_COUNT).contains(&n) => Some(format!("./src/puzzles/nono{}.txt", n)),
+        _ => None,
     }
 }
 
-//print the vertical and horizonal keys in the terminal
-fn print_keys<W: Write>(b: &Board, hkey_max: usize, vkey_max: usize, stdout: &mut W) {
-    write!(stdout, "{}", termion::clear::All).expect("Could not display keys");
-    for (i, key_set) in b.v_keys.iter().enumerate() {
-        for (space, (j, key)) in key_set.iter().rev().enumerate().enumerate() {
-            write!(
-                stdout,
-                "{}",
-                termion::cursor::Goto(
-                    (vkey_max + (vkey_max - 1) - j - space).try_into().unwrap(),
-                    u16::try_from((i + 1) * 2 + hkey_max - 1).ok().unwrap()
-                )
-            )
-            .expect("Could not display vertical keys");
-            println!("{}", key.value);
+//solves every file given on the command line, returning the worst outcome
+fn solve_files(options: &Options) -> Outcome {
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => match File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                if !options.quiet {
+                    eprintln!("{}: {}", path, e);
+                }
+                return Outcome::Invalid;
+            }
+        },
+        None if options.format == Format::Terminal && !options.quiet => {
+            match stdout().into_raw_mode() {
+                Ok(raw) => Box::new(raw),
+                Err(_) => Box::new(stdout()),
+            }
         }
-    }
+        None => Box::new(stdout()),
+    };
 
-    for (space, (i, key_set)) in b.h_keys.iter().enumerate().enumerate() {
-        for (j, key) in key_set.iter().rev().enumerate() {
-            write!(
-                stdout,
-                "{}",
-                termion::cursor::Goto(
-                    (i + 1 + vkey_max + vkey_max - 1 + (space * 4))
-                        .try_into()
-                        .unwrap(),
-                    u16::try_from(hkey_max - j).ok().unwrap()
-                )
-            )
-            .expect("Could not display horizontal keys");
-            println!("{}", key.value);
-        }
+    let mut worst = Outcome::Solved;
+    for file in options.files.iter() {
+        let outcome = solve_file(file, options, &mut out);
+        worst = cmp::max(worst, outcome);
     }
+    out.flush().expect("Could not write output");
+    worst
 }
 
-//check that the terminal is large enough to fit the given puzzle
-fn init(board: &Board) -> bool {
-    let termsize = termion::terminal_size().ok();
-    let termwidth = termsize.map(|(w, _)| w - 2).unwrap();
-    let termheight = termsize.map(|(_, h)| h - 2).unwrap();
-    if board.v_keys.len() * 2 + 6 > termheight.into() {
-        return false;
-    }
-    if board.h_keys.len() * 2 + 6 > termwidth.into() {
-        return false;
+//A puzzle file read by load_puzzle
+enum Loaded {
+    Plain(Puzzle),
+    Color(ColorPuzzle),
+}
+
+//reads a puzzle file in any of the formats read, picked by its content
+fn load_puzzle(file: &str) -> Result<Loaded, ParseError> {
+    let text = fs::read_to_string(file).unwrap_or_default();
+    if is_webpbn(&text) {
+        read_webpbn(file).map(|imported| match imported.to_puzzle() {
+            Some(puzzle) => Loaded::Plain(puzzle),
+            None => Loaded::Color(imported.puzzle),
+        })
+    } else if is_color_nonogram(&text) {
+        read_color_nonogram(file).map(Loaded::Color)
+    } else {
+        read_nonogram(file).map(Loaded::Plain)
     }
-    true
 }
 
-//solves the given puzzle using deductive methods until no more deductions are possible
-//once deductive reasoning cannot be used, make a guess, check whether the guess makes for a valid solution
-//if valid, keep using deduction, otherwise, pop from the stack and make another guess
-fn find_solution<W: Write>(mut b: Board, vkey_max: usize, hkey_max: usize, mut stdout: W) -> bool {
-    b.size.swap(0, 1);
-    b.solve();
-    let mut grid = Vec::new();
-    let mut guess = Vec::new();
-    grid.push(b);
-    guess.push(None);
-    while !grid.is_empty() {
-        let a_grid = grid.last().unwrap();
-        if !a_grid.valid_grid() {
-            grid.pop();
-            guess.pop();
-            continue;
-        }
-        if a_grid.complete_grid() {
-            a_grid.update_board(&mut stdout, &vkey_max, &hkey_max);
-            stdout.flush().unwrap();
-            return true;
+//loads a puzzle for a command that only handles black and white ones, reporting why it cannot
+fn load_plain_puzzle(file: &str, command: &str) -> Result<Puzzle, Outcome> {
+    match load_puzzle(file) {
+        Ok(Loaded::Plain(puzzle)) => Ok(puzzle),
+        Ok(Loaded::Color(_)) => {
+            eprintln!(
+                "{}: {} only handles black and white puzzles, not colored ones",
+                file, command
+            );
+            Err(Outcome::Invalid)
         }
-        if let Some((_, Status::Marked)) = guess.last().unwrap() {
-            grid.pop();
-            guess.pop();
-            continue;
-        }
-
-        let a_guess = guess.last_mut().unwrap();
-        if a_guess.is_none() {
-            let (i, j) = a_grid.get_unknown_cell();
-            *a_guess = Some(((i, j), Status::Empty));
-            let mut new_grid = a_grid.clone();
-            new_grid.grid[i][j].cell_state = Status::Empty;
-            new_grid.solve();
-            grid.push(new_grid);
-            guess.push(None);
-        } else {
-            let (i, j) = a_grid.get_unknown_cell();
-            *a_guess = Some(((i, j), Status::Marked));
-            let mut new_grid = a_grid.clone();
-            new_grid.grid[i][j].cell_state = Status::Marked;
-            new_grid.solve();
-            new_grid.solve();
-            grid.push(new_grid);
-            guess.push(None);
+        Err(e) => {
+            report_parse_error(file, &e);
+            Err(Outcome::Invalid)
         }
     }
-    false
 }
-impl Board {
-    ///Returns true if all the cells in the grid are set to either marked or empty
-    fn complete_grid(&self) -> bool {
-        for i in 0..self.size[0] {
-            for j in 0..self.size[1] {
-                if matches!(self.grid[i][j].cell_state, Status::Unknown) {
-                    return false;
-                }
+
+fn solve_file<W: Write>(file: &str, options: &Options, out: &mut W) -> Outcome {
+    match load_puzzle(file) {
+        Ok(Loaded::Plain(puzzle)) => solve_puzzle(file, &puzzle, options, out),
+        Ok(Loaded::Color(puzzle)) => solve_color_puzzle(file, &puzzle, options, out),
+        Err(e) => {
+            if !options.quiet {
+                report_parse_error(file, &e);
             }
+            write_report(out, file, options, Verdict::Invalid, Vec::new(), None);
+            Outcome::Invalid
         }
-        true
     }
+}
 
-    //read the nonogram in the given file
-    pub fn read_nonogram(&mut self, file: String) -> std::io::Result<()> {
-        let mut size = Vec::<usize>::new();
-        let mut h_keys: Vec<Vec<NonoKey>> = Vec::new();
-        let mut v_keys: Vec<Vec<NonoKey>> = Vec::new();
-        let file = File::open(file)?;
-        let reader = BufReader::new(file);
-        for (count, line) in reader.lines().enumerate() {
-            if count == 0 {
-                let line = line.unwrap();
-                let value = line.split(',');
-                size = value.map(|s| s.parse().unwrap()).collect();
-            } else if count > 0 && count <= size[0] {
-                let line = line.unwrap();
-                let value = line.split(',');
-                let temp_v: Vec<usize> = value.map(|s| s.parse().unwrap()).collect();
-                let v: Vec<NonoKey> = (0..temp_v.len())
-                    .map(|x| NonoKey {
-                        value: temp_v[x],
-                        lower_bound: 0,
-                        upper_bound: 0,
-                    })
-                    .collect();
-
-                h_keys.push(v);
+fn solve_puzzle<W: Write>(file: &str, puzzle: &Puzzle, options: &Options, out: &mut W) -> Outcome {
+    let report = |message: &dyn fmt::Display| {
+        if !options.quiet {
+            eprintln!("{}: {}", file, message);
+        }
+    };
+    if let Err(errors) = puzzle.validate() {
+        for e in errors.iter() {
+            report(e);
+        }
+        write_report(out, file, options, Verdict::Invalid, Vec::new(), None);
+        return Outcome::Invalid;
+    }
+    if options.blank && !write_svg(file, puzzle, None, options) {
+        return Outcome::Invalid;
+    }
+    let solve_options = SolveOptions {
+        engine: options.engine,
+        branching: options.branching,
+        level: options.level,
+        limits: Limits {
+            deadline: options
+                .timeout
+                .and_then(|timeout| Instant::now().checked_add(timeout)),
+            ..Default::default()
+        },
+        threads: options.threads,
+    };
+    let limit = options.solutions;
+    let (solutions, partial, stats) = puzzle.solutions_outcome(&solve_options, limit);
+    if let Some(format) = options.stats {
+        report_stats(file, &stats, format);
+    }
+    if let Some(partial) = partial {
+        report(&"timed out");
+        write_bitmaps(file, &partial, options);
+        if !options.quiet && options.format != Format::Terminal {
+            if options.format == Format::Json {
+                let partial = vec![partial.into()];
+                write_report(out, file, options, Verdict::Timeout, partial, Some(stats));
             } else {
-                let line = line.unwrap();
-                let value = line.split(',');
-                let temp_v: Vec<usize> = value.map(|s| s.parse().unwrap()).collect();
-                let v: Vec<NonoKey> = (0..temp_v.len())
-                    .map(|x| NonoKey {
-                        value: temp_v[x],
-                        lower_bound: 0,
-                        upper_bound: 0,
-                    })
-                    .collect();
-                v_keys.push(v);
+                writeln!(out, "cells found before timing out:\n{}", partial)
+                    .expect("Could not write output");
             }
         }
-        let grid = vec![
-            vec![
-                Point {
-                    cell_state: Status::Unknown
-                };
-                size[0]
-            ];
-            size[1]
-        ];
-
-        self.grid = grid;
-        self.h_keys = h_keys;
-        self.v_keys = v_keys;
-        self.size = size;
-        Ok(())
+        return Outcome::Timeout;
     }
-    //returns the maximum number of keys in a set
-    fn get_key_dimensions(&self, vkey: &mut usize, hkey: &mut usize) {
-        for i in self.v_keys.iter() {
-            if i.len() > *vkey {
-                *vkey = i.len();
-            }
+    let solution = match solutions.first() {
+        Some(solution) => solution,
+        None => {
+            report(&"no solution");
+            write_report(
+                out,
+                file,
+                options,
+                Verdict::Unsolvable,
+                Vec::new(),
+                Some(stats),
+            );
+            return Outcome::Unsolvable;
         }
-        for i in self.h_keys.iter() {
-            if i.len() > *hkey {
-                *hkey = i.len();
+    };
+    let outcome = if solutions.len() > 1 {
+        report(&format!(
+            "found {} solutions{}",
+            solutions.len(),
+            if solutions.len() == limit {
+                " (limit reached)"
+            } else {
+                ""
             }
-        }
+        ));
+        Outcome::NotUnique
+    } else {
+        Outcome::Solved
+    };
+    if !options.blank && !write_svg(file, puzzle, Some(solution), options) {
+        return Outcome::Invalid;
     }
-
-    //loops until no further deductive steps are possible to make progress on the solution
-    fn solve(&mut self) {
-        self.determine_bounds_v();
-        self.determine_bounds_h();
-        let mut progress = true;
-        while progress {
-            progress = self.definite_within_bounds_v()
-                | self.definite_within_bounds_h()
-                | self.determine_spaces_between_keys_v()
-                | self.determine_spaces_between_keys_h()
-                | self.separate_keys_v()
-                | self.separate_keys_h()
-                | self.tighten_bounds_v()
-                | self.tighten_bounds_h()
-                | self.complete_groups_v()
-                | self.complete_groups_h();
-        }
+    if !write_bitmaps(file, solution, options) {
+        return Outcome::Invalid;
+    }
+    if options.quiet {
+        return outcome;
     }
 
-    //figure out the bounds for each key by counting from the edgdes, considering preceding keys
-    fn determine_bounds_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let mut s = 0;
-            let len = self.v_keys[i].len();
-            for j in 0..len {
-                self.v_keys[i][j].upper_bound = s;
-                s += self.v_keys[i][j].value + 1; //number of spaces taken up by previous key value + space
-                progress = true;
+    let mut differences: Vec<(usize, usize)> = solutions[1..]
+        .iter()
+        .flat_map(|other| solution.differences(other))
+        .collect();
+    differences.sort_unstable();
+    differences.dedup();
+    match options.format {
+        Format::Text => {
+            if options.files.len() > 1 {
+                writeln!(out, "{}:", file).expect("Could not write output");
             }
-            s = 0;
-            for j in (0..len).rev() {
-                if width - s >= self.v_keys[i][j].value {
-                    self.v_keys[i][j].lower_bound = width - s;
-                    s += self.v_keys[i][j].value + 1;
-                    progress = true;
+            for (n, solution) in solutions.iter().enumerate() {
+                if solutions.len() > 1 {
+                    writeln!(out, "solution {}:", n + 1).expect("Could not write output");
                 }
+                writeln!(out, "{}", solution).expect("Could not write output");
             }
-        }
-        progress
-    }
-
-    fn determine_bounds_h(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let mut s = 0;
-            let len = self.h_keys[i].len();
-            for j in 0..len {
-                //from front to back
-                self.h_keys[i][j].upper_bound = s;
-                s += self.h_keys[i][j].value + 1; //number of spaces taken up by previous key value + space
-                progress = true;
-            }
-            s = 0;
-            for j in (0..len).rev() {
-                if height - s >= self.h_keys[i][j].value {
-                    self.h_keys[i][j].lower_bound = height - s;
-                    s += self.h_keys[i][j].value + 1;
-                    progress = true;
+            if !differences.is_empty() {
+                writeln!(out, "cells that differ (!):").expect("Could not write output");
+                let text = solution.to_string();
+                for (i, row) in text.lines().enumerate() {
+                    let row: String = row
+                        .chars()
+                        .enumerate()
+                        .map(|(j, c)| {
+                            if differences.binary_search(&(i, j)).is_ok() {
+                                '!'
+                            } else {
+                                c
+                            }
+                        })
+                        .collect();
+                    writeln!(out, "{}", row).expect("Could not write output");
                 }
+                writeln!(out).expect("Could not write output");
             }
         }
-        progress
+        Format::Terminal => {
+            let (vkey_max, hkey_max) = puzzle.key_dimensions();
+            update_board(puzzle, solution, &differences, out, vkey_max, hkey_max);
+        }
+        Format::Json => {
+            let rows = solutions.iter().cloned().map(Vec::from).collect();
+            write_report(out, file, options, verdict(outcome), rows, Some(stats));
+        }
     }
+    outcome
+}
 
-    //Set cells to marked if a given bound has definite marked cells within it.
-    //If the bound indicates that the entire key has been marked, mark the bounds as empty.
-    fn definite_within_bounds_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let len = self.v_keys[i].len();
-            for j in 0..len {
-                let def_upper = self.v_keys[i][j].lower_bound - self.v_keys[i][j].value;
-                let def_lower = self.v_keys[i][j].upper_bound + self.v_keys[i][j].value;
-
-                if def_lower >= def_upper {
-                    for n in def_upper..def_lower {
-                        if n >= width {
-                            continue;
-                        }
-                        if matches!(self.grid[i][n].cell_state, Status::Unknown) {
-                            self.grid[i][n].cell_state = Status::Marked;
-                            progress = true;
-                        }
-                    }
-                    if def_lower - def_upper == self.v_keys[i][j].value {
-                        if def_lower < width - 1
-                            && matches!(self.grid[i][def_lower].cell_state, Status::Unknown)
-                        {
-                            self.grid[i][def_lower].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                        if def_upper > 0
-                            && matches!(self.grid[i][def_upper - 1].cell_state, Status::Unknown)
-                        {
-                            self.grid[i][def_upper - 1].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                    }
-                }
-            }
+//solves a colored puzzle, rejecting the solver options that only apply to black and white ones
+fn solve_color_puzzle<W: Write>(
+    file: &str,
+    puzzle: &ColorPuzzle,
+    options: &Options,
+    out: &mut W,
+) -> Outcome {
+    let report = |message: &dyn fmt::Display| {
+        if !options.quiet {
+            eprintln!("{}: {}", file, message);
+        }
+    };
+    if let Err(errors) = puzzle.validate() {
+        for e in errors.iter() {
+            report(e);
         }
-        progress
+        write_report(out, file, options, Verdict::Invalid, Vec::new(), None);
+        return Outcome::Invalid;
     }
-    fn definite_within_bounds_h(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let len = self.h_keys[i].len();
-            for j in 0..len {
-                if self.h_keys[i][j].lower_bound < self.h_keys[i][j].value {
-                    continue;
-                }
-                let def_upper = self.h_keys[i][j].lower_bound - self.h_keys[i][j].value;
-                let def_lower = self.h_keys[i][j].upper_bound + self.h_keys[i][j].value;
-                if def_lower > def_upper {
-                    for n in def_upper..def_lower {
-                        if n >= height {
-                            continue;
-                        }
-                        if matches!(self.grid[n][i].cell_state, Status::Unknown) {
-                            self.grid[n][i].cell_state = Status::Marked;
-                            progress = true;
-                        }
-                    }
-                    if def_lower - def_upper == self.h_keys[i][j].value {
-                        if def_lower < height - 1
-                            && matches!(self.grid[def_lower][i].cell_state, Status::Unknown)
-                        {
-                            self.grid[def_lower][i].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                        if def_upper > 0
-                            && matches!(self.grid[def_upper - 1][i].cell_state, Status::Unknown)
-                        {
-                            self.grid[def_upper - 1][i].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                    }
-                }
+    let unsupported = [
+        ("--engine", options.engine != Engine::default()),
+        ("--branching", options.branching != Branching::default()),
+        ("--level", options.level != Level::default()),
+        ("--threads", options.threads != 1),
+    ];
+    if let Some((flag, _)) = unsupported.iter().find(|(_, given)| *given) {
+        report(&format!("{} only applies to black and white puzzles", flag));
+        write_report(out, file, options, Verdict::Invalid, Vec::new(), None);
+        return Outcome::Invalid;
+    }
+    if options.svg.is_some() {
+        report(&"SVG images are only drawn for black and white puzzles");
+    }
+    if options.png.is_some() || options.pbm.is_some() || options.pgm.is_some() {
+        report(&"bitmaps are only written for black and white puzzles");
+    }
+    let limits = Limits {
+        deadline: options
+            .timeout
+            .and_then(|timeout| Instant::now().checked_add(timeout)),
+        ..Default::default()
+    };
+    let limit = options.solutions;
+    let (solutions, partial, stats) = puzzle.solutions_with_limits(&limits, limit);
+    if let Some(format) = options.stats {
+        report_stats(file, &stats, format);
+    }
+    let rows = |solution: &ColorSolution| solution.to_string().lines().map(String::from).collect();
+    if let Some(partial) = partial {
+        report(&"timed out");
+        if !options.quiet && options.format != Format::Terminal {
+            if options.format == Format::Json {
+                let partial = vec![rows(&partial)];
+                write_report(out, file, options, Verdict::Timeout, partial, Some(stats));
+            } else {
+                writeln!(out, "cells found before timing out:\n{}", partial)
+                    .expect("Could not write output");
             }
         }
-        progress
+        return Outcome::Timeout;
+    }
+    if solutions.is_empty() {
+        report(&"no solution");
+        write_report(
+            out,
+            file,
+            options,
+            Verdict::Unsolvable,
+            Vec::new(),
+            Some(stats),
+        );
+        return Outcome::Unsolvable;
+    }
+    let outcome = if solutions.len() > 1 {
+        report(&format!("found {} solutions", solutions.len()));
+        Outcome::NotUnique
+    } else {
+        Outcome::Solved
+    };
+    if options.quiet {
+        return outcome;
+    }
+    if options.format == Format::Json {
+        let rows = solutions.iter().map(rows).collect();
+        write_report(out, file, options, verdict(outcome), rows, Some(stats));
+        return outcome;
+    }
+    if options.files.len() > 1 {
+        write!(out, "{}:{}", file, line_end(options.format)).expect("Could not write output");
     }
+    for (n, solution) in solutions.iter().enumerate() {
+        if solutions.len() > 1 {
+            write!(out, "solution {}:{}", n + 1, line_end(options.format))
+                .expect("Could not write output");
+        }
+        match options.format {
+            Format::Text | Format::Json => writeln!(out, "{}", solution),
+            Format::Terminal => draw_color_solution(solution, out),
+        }
+        .expect("Could not write output");
+    }
+    outcome
+}
 
-    //Zone key bounds further according to known empty cells
-    fn separate_keys_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let len = self.v_keys[i].len();
-            for j in 0..len {
-                let mut key = &mut self.v_keys[i][j];
-                for n in key.upper_bound..key.lower_bound {
-                    match self.grid[i][n].cell_state {
-                        Status::Empty => {
-                            if n > key.lower_bound {
-                                continue;
-                            }
-                            let upper_space = n - key.upper_bound;
-                            let lower_space = key.lower_bound - 1 - n;
+//the terminal is in raw mode for the terminal format, so lines also need a carriage return
+fn line_end(format: Format) -> &'static str {
+    match format {
+        Format::Text | Format::Json => "\n",
+        Format::Terminal => "\r\n",
+    }
+}
 
-                            if upper_space < key.value {
-                                key.upper_bound = n + 1;
-                                progress = true;
-                            }
-                            if lower_space < key.value && n >= key.value {
-                                key.lower_bound = n;
-                                progress = true;
-                            }
-                        }
-                        _ => continue,
-                    }
-                }
+//writes the SVG image asked for with --svg, if any, returning false if it could not be written
+fn write_svg(file: &str, puzzle: &Puzzle, solution: Option<&Solution>, options: &Options) -> bool {
+    let path = match &options.svg {
+        Some(path) => image_path(path, file, options.files.len() > 1),
+        None => return true,
+    };
+    let svg_options = SvgOptions {
+        guides: options.guides,
+        ..Default::default()
+    };
+    match fs::write(&path, render_svg(puzzle, solution, &svg_options)) {
+        Ok(()) => true,
+        Err(e) => {
+            if !options.quiet {
+                eprintln!("{}: {}", path.display(), e);
             }
+            false
         }
-        progress
     }
-    fn separate_keys_h(&mut self) -> bool {
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let len = self.h_keys[i].len();
-            for j in 0..len {
-                let key = &mut self.h_keys[i][j];
-                for n in key.upper_bound..key.lower_bound {
-                    match self.grid[n][i].cell_state {
-                        Status::Empty => {
-                            if n > key.lower_bound {
-                                continue;
-                            }
-                            let upper_space = n - key.upper_bound;
-                            let lower_space = key.lower_bound - n - 1;
+}
 
-                            if upper_space < key.value {
-                                key.upper_bound = n + 1;
-                                progress = true;
-                            }
-                            if lower_space < key.value && n >= key.value {
-                                key.lower_bound = n;
-                                progress = true;
-                            }
-                        }
-                        _ => continue,
-                    }
-                }
+//writes the bitmaps asked for with --png, --pbm and --pgm, returning false if one could not be
+//written
+fn write_bitmaps(file: &str, solution: &Solution, options: &Options) -> bool {
+    type Writer = fn(&Solution, usize, File) -> io::Result<()>;
+    let bitmaps: [(&Option<String>, Writer); 3] = [
+        (&options.png, write_png),
+        (&options.pbm, write_pbm),
+        (&options.pgm, write_pgm),
+    ];
+    for (path, writer) in bitmaps.iter() {
+        let path = match path {
+            Some(path) => image_path(path, file, options.files.len() > 1),
+            None => continue,
+        };
+        if let Err(e) = File::create(&path).and_then(|f| writer(solution, options.scale, f)) {
+            if !options.quiet {
+                eprintln!("{}: {}", path.display(), e);
             }
+            return false;
         }
-        progress
     }
+    true
+}
 
-    //Marks spaces between keys in a key set if their bounds don't intersect
-    fn determine_spaces_between_keys_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let len = self.v_keys[i].len();
-            let mut prev_lower_bound = self.v_keys[i][0].lower_bound;
-            for j in 1..len {
-                if self.v_keys[i][j].upper_bound > prev_lower_bound {
-                    for n in prev_lower_bound..self.v_keys[i][j].upper_bound {
-                        if matches!(self.grid[i][n].cell_state, Status::Unknown) {
-                            self.grid[i][n].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                    }
-                }
-                prev_lower_bound = self.v_keys[i][j].lower_bound;
-            }
-        }
-        progress
+//the path to write the image of a puzzle to: the path given, or when solving several puzzles,
+//that path with the name of the puzzle file added before the extension
+fn image_path(path: &str, file: &str, several: bool) -> PathBuf {
+    let path = PathBuf::from(path);
+    if !several {
+        return path;
     }
-    fn determine_spaces_between_keys_h(&mut self) -> bool {
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let len = self.h_keys[i].len();
-            let mut prev_lower_bound = self.h_keys[i][0].lower_bound;
-            for j in 1..len {
-                if self.h_keys[i][j].upper_bound > prev_lower_bound {
-                    for n in prev_lower_bound..self.h_keys[i][j].upper_bound {
-                        if matches!(self.grid[n][i].cell_state, Status::Unknown) {
-                            self.grid[n][i].cell_state = Status::Empty;
-                            progress = true;
-                        }
-                    }
-                }
-                prev_lower_bound = self.h_keys[i][j].lower_bound;
-            }
-        }
-        progress
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let puzzle = Path::new(file)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let mut name = format!("{}-{}", stem, puzzle);
+    if let Some(extension) = path.extension() {
+        name = format!("{}.{}", name, extension.to_string_lossy());
     }
+    path.with_file_name(name)
+}
 
-    //Further reduce key bounds if it contains a marked cell that is exclusive to the given bound
-    fn tighten_bounds_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let len = self.v_keys[i].len();
-            let mut prev_bound;
-            let mut next_bound;
-            for j in 0..len {
-                if j < len - 1 {
-                    next_bound = self.v_keys[i][j + 1].upper_bound;
-                } else {
-                    next_bound = self.v_keys[i][j].lower_bound;
-                }
+//writes the result of a puzzle as one line of JSON for the json format, see SolveReport
+fn write_report<W: Write>(
+    out: &mut W,
+    file: &str,
+    options: &Options,
+    verdict: Verdict,
+    solutions: Vec<Vec<String>>,
+    stats: Option<SolveStats>,
+) {
+    if options.format != Format::Json || options.quiet {
+        return;
+    }
+    let report = SolveReport {
+        file: Some(file.to_string()),
+        verdict,
+        solutions,
+        stats,
+    };
+    let json = serde_json::to_string(&report).expect("a report is valid JSON");
+    writeln!(out, "{}", json).expect("Could not write output");
+}
 
-                if j > 0 {
-                    prev_bound = self.v_keys[i][j - 1].lower_bound;
-                } else {
-                    prev_bound = self.v_keys[i][j].upper_bound;
-                }
+fn verdict(outcome: Outcome) -> Verdict {
+    match outcome {
+        Outcome::Solved => Verdict::Solved,
+        Outcome::Unsolvable => Verdict::Unsolvable,
+        Outcome::Invalid => Verdict::Invalid,
+        Outcome::Timeout => Verdict::Timeout,
+        Outcome::NotUnique => Verdict::NotUnique,
+    }
+}
 
-                let key = &mut self.v_keys[i][j];
-                let min_def = cmp::min(key.lower_bound, prev_bound);
-                let max_def = cmp::max(key.upper_bound, next_bound);
-                if min_def >= max_def {
-                    continue;
-                }
-                if min_def < max_def {
-                    for n in min_def..max_def {
-                        if matches!(self.grid[i][n].cell_state, Status::Marked) {
-                            let mut upper = 0;
-                            if n >= key.value {
-                                upper = n - key.value;
-                            }
-                            let lower = n + key.value;
-                            if key.upper_bound < upper {
-                                key.upper_bound = upper;
-                                progress = true;
-                            }
-                            if key.lower_bound > lower && lower >= key.value {
-                                key.lower_bound = lower;
-                                progress = true;
-                            }
-                        }
-                    }
-                }
-            }
+//Draws a colored solution in true color, two characters per cell and white for the background
+fn draw_color_solution<W: Write>(solution: &ColorSolution, out: &mut W) -> io::Result<()> {
+    for row in 0..solution.height() {
+        for column in 0..solution.width() {
+            let [r, g, b] = solution
+                .color(row, column)
+                .map_or([255, 255, 255], |color| color.rgb);
+            write!(
+                out,
+                "{}{}{}",
+                color::Fg(color::Rgb(r, g, b)),
+                COLOR_CELL,
+                color::Fg(color::Reset)
+            )?;
         }
-        progress
+        write!(out, "\r\n")?;
     }
-    fn tighten_bounds_h(&mut self) -> bool {
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let len = self.h_keys[i].len();
-            let mut prev_bound;
-            let mut next_bound;
-            for j in 0..len {
-                if j < len - 1 {
-                    next_bound = self.h_keys[i][j + 1].upper_bound;
-                } else {
-                    next_bound = self.h_keys[i][j].lower_bound;
-                }
-
-                if j > 0 {
-                    prev_bound = self.h_keys[i][j - 1].lower_bound;
-                } else {
-                    prev_bound = self.h_keys[i][j].upper_bound;
-                }
-
-                let key = &mut self.h_keys[i][j];
-                let min_def = cmp::min(key.lower_bound, prev_bound);
-                let max_def = cmp::max(key.upper_bound, next_bound);
-                if min_def >= max_def {
-                    continue;
-                }
+    write!(out, "\r\n")
+}
 
-                if max_def > min_def {
-                    for n in min_def..max_def {
-                        if matches!(self.grid[n][i].cell_state, Status::Marked) {
-                            let mut upper = 0;
-                            if n >= key.value {
-                                upper = n - key.value;
-                            }
-                            let lower = n + key.value;
-                            if key.upper_bound < upper {
-                                key.upper_bound = upper;
-                                progress = true;
-                            }
-                            if key.lower_bound > lower && lower >= key.value {
-                                key.lower_bound = lower;
-                                progress = true;
-                            }
-                        }
-                    }
-                }
-            }
+//prints a parse error, underlining the offending token in the line of the file it was found on
+fn report_parse_error(file: &str, e: &ParseError) {
+    let (line, column) = match (e.line(), e.column()) {
+        (Some(line), Some(column)) => (line, column),
+        _ => {
+            eprintln!("{}: {}", file, e);
+            return;
         }
-        progress
+    };
+    eprintln!("{}:{}:{}: {}", file, line, column, e);
+    let text = fs::read_to_string(file)
+        .ok()
+        .and_then(|text| text.lines().nth(line - 1).map(String::from));
+    if let Some(text) = text {
+        let width = e
+            .token()
+            .map_or(1, |token| cmp::max(token.chars().count(), 1));
+        eprintln!("{:>5} | {}", line, text);
+        eprintln!("      | {}{}", " ".repeat(column - 1), "^".repeat(width));
     }
+}
 
-    //Mark the remaining cells in the row/cell if all the keys in the set have been determined
-    fn complete_groups_v(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..height {
-            let len = self.v_keys[i].len();
-            let mut complete = 0;
-            let mut cell_state = 0;
-            for j in 0..width {
-                match self.grid[i][j].cell_state {
-                    Status::Marked => {
-                        if cell_state == 0 {
-                            cell_state = 1;
-                        }
-                    }
-                    Status::Empty => {
-                        if cell_state == 1 {
-                            complete += 1;
-                        }
-                        cell_state = 0;
-                    }
-                    Status::Unknown => cell_state = 2,
-                }
-            }
-            if complete == len {
-                for n in 0..width {
-                    if matches!(self.grid[i][n].cell_state, Status::Unknown) {
-                        progress = true;
-                        self.grid[i][n].cell_state = Status::Empty;
-                    }
-                }
+//prints the solver counters for a puzzle to stderr
+fn report_stats(file: &str, stats: &SolveStats, format: StatsFormat) {
+    let fixed = &stats.fixed;
+    match format {
+        StatsFormat::Text => {
+            let line = |name: &str, value: &dyn fmt::Display| eprintln!("  {:<26}{}", name, value);
+            eprintln!("{}:", file);
+            line("rounds", &stats.rounds);
+            line("lines solved", &stats.lines_solved);
+            eprintln!("  cells fixed by rule:");
+            line("  definite within bounds", &fixed.definite_within_bounds);
+            line("  spaces between keys", &fixed.spaces_between_keys);
+            line("  separate keys", &fixed.separate_keys);
+            line("  tighten bounds", &fixed.tighten_bounds);
+            line("  complete groups", &fixed.complete_groups);
+            line("  line solver", &fixed.line_solver);
+            line("  probing", &fixed.probing);
+            line("probes", &stats.probes);
+            line("guesses", &stats.guesses);
+            line("nodes", &stats.nodes);
+            line("backtracks", &stats.backtracks);
+            line("max depth", &stats.max_depth);
+            line("elapsed", &format!("{:.3?}", stats.elapsed));
+            if stats.interrupted {
+                eprintln!("  interrupted by the timeout");
             }
         }
-        progress
-    }
-    fn complete_groups_h(&mut self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut progress = false;
-        for i in 0..width {
-            let mut complete = 0;
-            let mut cell_state = 0;
-            for j in 0..height {
-                match self.grid[j][i].cell_state {
-                    Status::Marked => {
-                        if cell_state == 0 {
-                            cell_state = 1;
-                        }
-                    }
-                    Status::Empty => {
-                        if cell_state == 1 {
-                            complete += 1;
-                        }
-                        cell_state = 0;
-                    }
-                    Status::Unknown => cell_state = 2,
-                }
-            }
-            if complete == self.h_keys[i].len() {
-                for n in 0..height {
-                    if matches!(self.grid[n][i].cell_state, Status::Unknown) {
-                        progress = true;
-                        self.grid[n][i].cell_state = Status::Empty;
-                    }
-                }
-            }
+        StatsFormat::Json => {
+            let line = StatsLine { file, stats };
+            let json = serde_json::to_string(&line).expect("stats are valid JSON");
+            eprintln!("{}", json);
         }
-        progress
     }
+}
 
-    //Return true if the pattern in keys matches the pattern in compare_to
-    fn compare_keys_start(&self, keys: &[usize], compare_to: &[NonoKey]) -> bool {
-        let len = keys.len();
-        if keys.len() > compare_to.len() {
-            return false;
-        }
+//A line of --stats json, the counters of a puzzle along with its file
+#[derive(Serialize)]
+struct StatsLine<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    stats: &'a SolveStats,
+}
 
-        for i in 0..len {
-            if keys[i] != compare_to[i].value {
-                return false;
-            }
+//prints the deduction log of a puzzle, indenting the steps that rely on guesses
+fn explain_file(file: &str) -> Outcome {
+    let puzzle = match load_plain_puzzle(file, "explain") {
+        Ok(puzzle) => puzzle,
+        Err(outcome) => return outcome,
+    };
+    if let Err(errors) = puzzle.validate() {
+        for e in errors.iter() {
+            eprintln!("{}: {}", file, e);
         }
-        true
+        return Outcome::Invalid;
     }
-    //Return true if both vectors match each other entirely
-    fn compare_keys_whole(&self, keys: &[usize], compare_to: &[NonoKey]) -> bool {
-        if keys.len() != compare_to.len() {
-            return false;
+    let (solution, trace) = puzzle.solve_traced();
+    for (n, step) in trace.steps().iter().enumerate() {
+        println!("{:>5}  {}{}", n + 1, "  ".repeat(step.depth()), step);
+    }
+    let logic = trace
+        .steps()
+        .iter()
+        .filter(|step| step.depth() == 0 && !step.is_guess())
+        .count();
+    let guesses = trace.guesses();
+    println!(
+        "\n{} steps by logic alone, {} guess{}",
+        logic,
+        guesses,
+        if guesses == 1 { "" } else { "es" }
+    );
+    match solution {
+        Some(solution) => {
+            println!("\n{}", solution);
+            Outcome::Solved
         }
-        let len = keys.len();
-        for i in 0..len {
-            if keys[i] != compare_to[i].value {
-                return false;
-            }
+        None => {
+            eprintln!("{}: no solution", file);
+            Outcome::Unsolvable
         }
-        true
     }
+}
 
-    //Return false if a row/column consists of cells that don't correspond with the appropriate key set
-    fn valid_grid(&self) -> bool {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        let mut keys = Vec::with_capacity(10);
-        for i in 0..height {
-            keys.clear();
-            let mut group = 0;
-            let mut t = false;
-            for j in 0..width {
-                match self.grid[i][j].cell_state {
-                    Status::Marked => {
-                        group += 1;
-                    }
-                    Status::Empty => {
-                        if group > 0 {
-                            keys.push(group);
-                        }
-                        group = 0;
-                    }
-                    Status::Unknown => {
-                        t = true;
-                        break;
-                    }
-                }
+//prints the difficulty of every file, along with the guesses and depth of search it needs
+fn rate_files(files: &[String]) -> Outcome {
+    let mut worst = Outcome::Solved;
+    for file in files.iter() {
+        let puzzle = match load_plain_puzzle(file, "rate") {
+            Ok(puzzle) => puzzle,
+            Err(outcome) => {
+                worst = cmp::max(worst, outcome);
+                continue;
             }
+        };
+        if let Err(errors) = puzzle.validate() {
+            for e in errors.iter() {
+                eprintln!("{}: {}", file, e);
+            }
+            worst = cmp::max(worst, Outcome::Invalid);
+            continue;
+        }
+        let rating = match rate_puzzle(&puzzle) {
+            Some(rating) => rating,
+            None => {
+                eprintln!("{}: no solution", file);
+                worst = cmp::max(worst, Outcome::Unsolvable);
+                continue;
+            }
+        };
+        println!(
+            "{}: {} (score {}), solved by {}, guesses: {}, depth: {}{}",
+            file,
+            rating.label(),
+            rating.score,
+            rating.difficulty,
+            rating.guesses,
+            rating.max_depth,
+            if rating.unique { "" } else { ", not unique" }
+        );
+        if !rating.unique {
+            worst = cmp::max(worst, Outcome::NotUnique);
+        }
+    }
+    worst
+}
 
-            if group > 0 && !t {
-                keys.push(group);
+//turns an image into a puzzle file and reports whether the puzzle has a single solution,
+//making it unique first if asked to
+fn convert_image(options: &ImageOptions) -> Outcome {
+    let file = &options.file;
+    let mut picture = match read_bitmap(file, options.threshold) {
+        Ok(picture) => picture,
+        Err(e) => {
+            report_parse_error(file, &e);
+            return Outcome::Invalid;
+        }
+    };
+    let mut puzzle = Puzzle::from_picture(&picture);
+    let solve_options = SolveOptions {
+        limits: Limits {
+            deadline: Instant::now().checked_add(options.timeout),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (solutions, partial, _) = puzzle.solutions_outcome(&solve_options, 2);
+    let unique = partial.is_none() && solutions.len() == 1;
+    eprintln!(
+        "{}: {}x{} puzzle, {}",
+        file,
+        puzzle.width(),
+        puzzle.height(),
+        if partial.is_some() {
+            "timed out checking whether the solution is unique"
+        } else if unique {
+            "unique solution"
+        } else {
+            "more than one solution"
+        }
+    );
+    let mut outcome = if partial.is_some() {
+        Outcome::Timeout
+    } else if unique {
+        Outcome::Solved
+    } else {
+        Outcome::NotUnique
+    };
+    match options.fix {
+        _ if outcome != Outcome::NotUnique => {}
+        None => {}
+        //the same deadline bounds the searches for the edits, the picture is written unchanged
+        //if they run out of time
+        Some(Fix::Givens) => match add_givens(&picture, &solve_options.limits) {
+            SolveOutcome::Solved(fixed) => {
+                report_edits(file, "given", &fixed);
+                puzzle = fixed.puzzle;
+                outcome = Outcome::Solved;
             }
-            if t {
-                if !self.compare_keys_start(&keys, &self.v_keys[i]) {
-                    return false;
-                }
-            } else {
-                if keys.is_empty() {
-                    keys.push(0);
-                }
-                if !self.compare_keys_whole(&keys, &self.v_keys[i]) {
-                    return false;
+            _ => {
+                eprintln!("{}: timed out picking givens", file);
+                outcome = Outcome::Timeout;
+            }
+        },
+        Some(Fix::Flips) => match flip_cells(&picture, &solve_options.limits) {
+            SolveOutcome::Solved(fixed) => {
+                for flip in fixed.edits.iter() {
+                    picture[flip.row][flip.column] = flip.marked;
                 }
+                report_edits(file, "flipped cell", &fixed);
+                puzzle = fixed.puzzle;
+                outcome = Outcome::Solved;
+            }
+            SolveOutcome::Unsat => {
+                eprintln!("{}: flipping cells did not make the solution unique", file)
+            }
+            SolveOutcome::Timeout(_) => {
+                eprintln!("{}: timed out picking cells to flip", file);
+                outcome = Outcome::Timeout;
             }
+        },
+    }
+
+    let text = puzzle_text(&puzzle, &picture, options.output.as_deref());
+    let written = match &options.output {
+        Some(path) => fs::write(path, text).map_err(|e| (path.as_str(), e)),
+        None => stdout()
+            .write_all(text.as_bytes())
+            .map_err(|e| ("stdout", e)),
+    };
+    if let Err((path, e)) = written {
+        eprintln!("{}: {}", path, e);
+        return Outcome::Invalid;
+    }
+    outcome
+}
+
+//lists the cells given or flipped to make a puzzle unique, with rows and columns from 1
+fn report_edits(file: &str, what: &str, fixed: &Edited) {
+    let edits = &fixed.edits;
+    eprintln!(
+        "{}: {} {}{} the solution unique, {}:",
+        file,
+        edits.len(),
+        what,
+        if edits.len() == 1 { " makes" } else { "s make" },
+        if fixed.minimal {
+            "no fewer would"
+        } else {
+            "none can be left out (too large a picture to look for fewer)"
         }
-        for i in 0..width {
-            keys.clear();
-            let mut group = 0;
-            let mut t = false;
-            for j in 0..height {
-                match self.grid[j][i].cell_state {
-                    Status::Marked => group += 1,
-                    Status::Empty => {
-                        if group > 0 {
-                            keys.push(group);
-                        }
-                        group = 0;
-                    }
-                    Status::Unknown => {
-                        t = true;
-                        break;
-                    }
-                }
+    );
+    for edit in edits {
+        eprintln!(
+            "    row {}, column {}: {}",
+            edit.row + 1,
+            edit.column + 1,
+            if edit.marked { "marked" } else { "empty" }
+        );
+    }
+}
+
+//generates puzzles with consecutive seeds, writing each one with its solution
+fn generate_puzzles(args: &GenerateArgs) -> Outcome {
+    let first_seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let options = GenerateOptions {
+        width: args.width,
+        height: args.height,
+        density: args.density,
+        difficulty: args.difficulty,
+        attempts: args.attempts,
+        ..Default::default()
+    };
+    let mut worst = Outcome::Solved;
+    for n in 0..args.count {
+        let seed = first_seed.wrapping_add(n as u64);
+        let generated = match generate_puzzle(&options, seed) {
+            Some(generated) => generated,
+            None => {
+                eprintln!(
+                    "seed {}: no {}puzzle found in {} attempts",
+                    seed,
+                    args.difficulty
+                        .map_or(String::new(), |d| format!("{} ", d.label())),
+                    args.attempts
+                );
+                worst = cmp::max(worst, Outcome::Timeout);
+                continue;
             }
-            if group > 0 && !t {
-                keys.push(group);
+        };
+        let picture: Vec<Vec<bool>> = generated
+            .solution
+            .rows()
+            .iter()
+            .map(|row| row.iter().map(|&state| state == Status::Marked).collect())
+            .collect();
+        let text = puzzle_text(&generated.puzzle, &picture, args.output.as_deref());
+        let written = match &args.output {
+            Some(path) => {
+                let path = image_path(path, &(n + 1).to_string(), args.count > 1);
+                fs::write(&path, text).map_err(|e| (path.display().to_string(), e))
             }
-            if t {
-                if !self.compare_keys_start(&keys, &self.h_keys[i]) {
-                    return false;
-                }
-            } else {
-                if keys.is_empty() {
-                    keys.push(0);
-                }
-                if !self.compare_keys_whole(&keys, &self.h_keys[i]) {
-                    return false;
-                }
+            None if n > 0 => {
+                writeln!(stdout(), "\n{}", text.trim_end()).map_err(|e| ("stdout".to_string(), e))
             }
+            None => write!(stdout(), "{}", text).map_err(|e| ("stdout".to_string(), e)),
+        };
+        if let Err((path, e)) = written {
+            eprintln!("{}: {}", path, e);
+            return Outcome::Invalid;
         }
+        let rating = generated.rating;
+        eprintln!(
+            "seed {}: {}x{} puzzle, {} (score {}), {} attempt{}",
+            seed,
+            args.width,
+            args.height,
+            rating.label(),
+            rating.score,
+            generated.attempts,
+            if generated.attempts == 1 { "" } else { "s" }
+        );
+    }
+    worst
+}
 
-        true
+//the puzzle in the format picked by the extension of the output file: a JSON or TOML document
+//holding the picture as its solution, or the comma separated clues of parse_nonogram, which
+//has no room for givens
+fn puzzle_text(puzzle: &Puzzle, picture: &[Vec<bool>], output: Option<&str>) -> String {
+    let extension = output
+        .and_then(|path| Path::new(path).extension())
+        .and_then(|extension| extension.to_str());
+    let mut document = PuzzleDocument::from(puzzle);
+    document.solution = Some(
+        picture
+            .iter()
+            .map(|row| row.iter().map(|&m| if m { '#' } else { '.' }).collect())
+            .collect(),
+    );
+    match extension {
+        Some("toml") => document.to_toml(),
+        Some("json") => document.to_json() + "\n",
+        _ if !document.givens.is_empty() => document.to_json() + "\n",
+        _ => puzzle.to_nonogram(),
     }
-    //Finds and returns the first unmarked cell in the grid
-    fn get_unknown_cell(&self) -> (usize, usize) {
-        let height = self.v_keys.len();
-        let width = self.h_keys.len();
-        for i in 0..height {
-            for j in 0..width {
-                if matches!(self.grid[i][j].cell_state, Status::Unknown) {
-                    return (i, j);
-                }
+}
+
+//solves every file with each branching strategy, printing the nodes explored and the time taken
+fn compare_files(files: &[String]) -> Outcome {
+    let mut worst = Outcome::Solved;
+    println!(
+        "{:<30} {:<12} {:>10} {:>12}",
+        "puzzle", "branching", "nodes", "time"
+    );
+    for file in files.iter() {
+        let puzzle = match load_plain_puzzle(file, "compare") {
+            Ok(puzzle) => puzzle,
+            Err(outcome) => {
+                worst = cmp::max(worst, outcome);
+                continue;
+            }
+        };
+        for (name, branching) in cli::BRANCHINGS.iter() {
+            let start = Instant::now();
+            let (solution, stats) = puzzle.solve_with(&SolveOptions {
+                branching: *branching,
+                ..Default::default()
+            });
+            if solution.is_none() {
+                worst = cmp::max(worst, Outcome::Unsolvable);
             }
+            println!(
+                "{:<30} {:<12} {:>10} {:>12.3?}",
+                file,
+                name,
+                stats.nodes,
+                start.elapsed()
+            );
         }
-        println!("cant find unknown");
-        (0, 0)
     }
+    worst
+}
+
+//print the vertical and horizonal keys in the terminal
+fn print_keys<W: Write>(puzzle: &Puzzle, hkey_max: usize, vkey_max: usize, stdout: &mut W) {
+    write!(stdout, "{}", termion::clear::All).expect("Could not display keys");
+    for (i, key_set) in puzzle.row_clues().iter().enumerate() {
+        for (space, (j, key)) in key_set.iter().rev().enumerate().enumerate() {
+            write!(
+                stdout,
+                "{}",
+                termion::cursor::Goto(
+                    (vkey_max + (vkey_max - 1) - j - space).try_into().unwrap(),
+                    u16::try_from((i + 1) * 2 + hkey_max - 1).ok().unwrap()
+                )
+            )
+            .expect("Could not display vertical keys");
+            write!(stdout, "{}", key).expect("Could not display vertical keys");
+        }
+    }
+
+    for (space, (i, key_set)) in puzzle.column_clues().iter().enumerate().enumerate() {
+        for (j, key) in key_set.iter().rev().enumerate() {
+            write!(
+                stdout,
+                "{}",
+                termion::cursor::Goto(
+                    (i + 1 + vkey_max + vkey_max - 1 + (space * 4))
+                        .try_into()
+                        .unwrap(),
+                    u16::try_from(hkey_max - j).ok().unwrap()
+                )
+            )
+            .expect("Could not display horizontal keys");
+            write!(stdout, "{}", key).expect("Could not display horizontal keys");
+        }
+    }
+}
+
+//check that the terminal is large enough to fit the given puzzle
+fn init(puzzle: &Puzzle) -> bool {
+    let termsize = termion::terminal_size().ok();
+    let termwidth = termsize.map(|(w, _)| w - 2).unwrap();
+    let termheight = termsize.map(|(_, h)| h - 2).unwrap();
+    if puzzle.height() * 2 + 6 > termheight.into() {
+        return false;
+    }
+    if puzzle.width() * 2 + 6 > termwidth.into() {
+        return false;
+    }
+    true
+}
 
-    //Prints the complete grid in the terminal
-    fn update_board<W: Write>(&self, stdout: &mut W, vkey_max: &usize, hkey_max: &usize) {
-        print_keys(&self, *hkey_max, *vkey_max, stdout);
-        for (i, cell_set) in self.grid.iter().enumerate() {
-            for (j, cell) in cell_set.iter().enumerate() {
+//Prints the complete grid in the terminal
+//cells in highlight are drawn in red
+fn update_board<W: Write>(
+    puzzle: &Puzzle,
+    solution: &Solution,
+    highlight: &[(usize, usize)],
+    stdout: &mut W,
+    vkey_max: usize,
+    hkey_max: usize,
+) {
+    print_keys(puzzle, hkey_max, vkey_max, stdout);
+    for (i, cell_set) in solution.rows().iter().enumerate() {
+        for (j, cell) in cell_set.iter().enumerate() {
+            for line in 0..2 {
                 write!(
                     stdout,
                     "{}",
@@ -829,542 +1006,51 @@ impl Board {
                         ((vkey_max + (vkey_max - 1) + 1) + (j * 5))
                             .try_into()
                             .unwrap(),
-                        u16::try_from(hkey_max + 1 + (i * 2)).ok().unwrap()
+                        u16::try_from(hkey_max + 1 + (i * 2) + line).ok().unwrap()
                     )
                 )
                 .expect("Error updating grid");
-                match cell.cell_state {
-                    Status::Empty => println!(
+                match cell {
+                    Status::Empty if highlight.contains(&(i, j)) => write!(
+                        stdout,
                         "{fg}{}{reset_fg}",
                         CELL,
-                        fg = color::Fg(color::White),
+                        fg = color::Fg(color::LightRed),
                         reset_fg = color::Fg(color::Reset)
                     ),
-                    Status::Marked => println!(
+                    Status::Marked if highlight.contains(&(i, j)) => write!(
+                        stdout,
                         "{fg}{}{reset_fg}",
                         CELL,
-                        fg = color::Fg(color::Black),
+                        fg = color::Fg(color::Red),
                         reset_fg = color::Fg(color::Reset)
                     ),
-                    Status::Unknown => println!("{}", UNKNOWN),
-                }
-                write!(
-                    stdout,
-                    "{}",
-                    termion::cursor::Goto(
-                        ((vkey_max + (vkey_max - 1) + 1) + (j * 5))
-                            .try_into()
-                            .unwrap(),
-                        u16::try_from(hkey_max + 1 + (i * 2) + 1).ok().unwrap()
-                    )
-                )
-                .expect("Error updating grid");
-                match cell.cell_state {
-                    Status::Empty => println!(
+                    Status::Empty => write!(
+                        stdout,
                         "{fg}{}{reset_fg}",
                         CELL,
                         fg = color::Fg(color::White),
                         reset_fg = color::Fg(color::Reset)
                     ),
-                    Status::Marked => println!(
+                    Status::Marked => write!(
+                        stdout,
                         "{fg}{}{reset_fg}",
                         CELL,
                         fg = color::Fg(color::Black),
                         reset_fg = color::Fg(color::Reset)
                     ),
-                    Status::Unknown => println!("{}", UNKNOWN),
+                    Status::Unknown => write!(stdout, "{}", UNKNOWN),
                 }
+                .expect("Error updating grid");
             }
         }
-        write!(
-            stdout,
-            "{}",
-            termion::cursor::Goto(1, (self.size[0] * 2 + 5).try_into().unwrap())
-        )
-        .expect("Error updating board");
-
-        stdout.flush().unwrap();
     }
-}
-
-#[test]
-fn complete_grid() {
-    let mut b: Board = Default::default();
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Marked
-            };
-            10
-        ];
-        10
-    ];
-    b.size = vec![10, 10];
-    let result = b.complete_grid();
-    assert_eq!(result, true);
-}
-#[test]
-fn incomplete_grid() {
-    let mut b: Board = Default::default();
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Marked
-            };
-            10
-        ];
-        10
-    ];
-    b.grid[0][9].cell_state = Status::Unknown;
-    b.size = vec![10, 10];
-    let result = b.complete_grid();
-    assert_eq!(result, false);
-}
-#[test]
-fn max_2d_vector() {
-    let mut b: Board = Default::default();
-    let mut v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 1,
-            upper_bound: 0,
-            lower_bound: 0
-        };
-        1
-    ];
-    b.h_keys.push(v);
-    v = vec![
-        NonoKey {
-            value: 1,
-            upper_bound: 0,
-            lower_bound: 0
-        };
-        5
-    ];
-    b.h_keys.push(v);
+    write!(
+        stdout,
+        "{}",
+        termion::cursor::Goto(1, (solution.height() * 2 + 5).try_into().unwrap())
+    )
+    .expect("Error updating board");
 
-    v = vec![
-        NonoKey {
-            value: 1,
-            upper_bound: 0,
-            lower_bound: 0
-        };
-        3
-    ];
-    b.v_keys.push(v);
-    v = vec![
-        NonoKey {
-            value: 1,
-            upper_bound: 0,
-            lower_bound: 0
-        };
-        7
-    ];
-    b.v_keys.push(v);
-    v = vec![
-        NonoKey {
-            value: 1,
-            upper_bound: 0,
-            lower_bound: 0
-        };
-        1
-    ];
-    b.v_keys.push(v);
-
-    let mut hmax = 0;
-    let mut vmax = 0;
-
-    b.get_key_dimensions(&mut vmax, &mut hmax);
-    assert_eq!(vmax, 7);
-    assert_eq!(hmax, 5);
-}
-#[test]
-fn valid_bounds() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 4,
-            upper_bound: 0,
-            lower_bound: 0,
-        },
-        NonoKey {
-            value: 7,
-            upper_bound: 0,
-            lower_bound: 0,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 1,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.size = vec![15, 1];
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    assert_eq!(b.v_keys[0][0].upper_bound, 0);
-    assert_eq!(b.v_keys[0][0].lower_bound, 7);
-    assert_eq!(b.v_keys[0][1].upper_bound, 5);
-    assert_eq!(b.v_keys[0][1].lower_bound, 15);
-}
-#[test]
-fn marked_bounds() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 4,
-            upper_bound: 0,
-            lower_bound: 0,
-        },
-        NonoKey {
-            value: 7,
-            upper_bound: 0,
-            lower_bound: 0,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 1,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.size = vec![1, 15];
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Unknown
-            };
-            15
-        ];
-        1
-    ];
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    b.definite_within_bounds_v();
-    b.definite_within_bounds_h();
-    assert_eq!(b.grid[0][3].cell_state, Status::Marked); //15 - 7 - 1 - 4 overlaps with -1+4
-    assert_eq!(b.grid[0][8].cell_state, Status::Marked); //the key "7" definitely exists within the bounds -1+4+1+7 through 15 - 7  (8 through 11)
-    assert_eq!(b.grid[0][9].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][10].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][11].cell_state, Status::Marked);
-}
-
-#[test]
-fn marked_and_separated_bounds() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 0,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.h_keys[1][0].value = 1;
-    b.h_keys[2][0].value = 1;
-    b.h_keys[10][0].value = 1;
-    b.h_keys[11][0].value = 1;
-    b.h_keys[12][0].value = 1;
-    b.size = vec![1, 15];
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Unknown
-            };
-            15
-        ];
-        1
-    ];
-    b.grid[0][6].cell_state = Status::Empty;
-    b.grid[0][1].cell_state = Status::Marked;
-    b.grid[0][11].cell_state = Status::Marked;
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    b.definite_within_bounds_v();
-    b.definite_within_bounds_h();
-    b.tighten_bounds_v();
-    b.tighten_bounds_h();
-    b.determine_spaces_between_keys_v();
-    b.determine_spaces_between_keys_h();
-    assert_eq!(b.grid[0][1].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][2].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][3].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][4].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][5].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][6].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][7].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][8].cell_state, Status::Empty);
-    assert_eq!(b.grid[0][10].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][11].cell_state, Status::Marked);
-    assert_eq!(b.grid[0][12].cell_state, Status::Marked);
-
-    assert_eq!(b.grid[0][0].cell_state, Status::Unknown);
-    assert_eq!(b.grid[0][13].cell_state, Status::Unknown);
-    assert_eq!(b.grid[0][14].cell_state, Status::Unknown);
-}
-
-#[test]
-fn whole_key_comparison() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-        NonoKey {
-            value: 4,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 5,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-    ];
-    b.v_keys.push(v);
-    let keys = vec![2, 3, 4, 5];
-    assert_eq!(b.compare_keys_whole(&keys, &b.v_keys[0]), true);
-}
-#[test]
-fn partial_key_comparison() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-        NonoKey {
-            value: 4,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 5,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-    ];
-    b.v_keys.push(v);
-    let keys = vec![2, 3];
-    assert_eq!(b.compare_keys_start(&keys, &b.v_keys[0]), true);
-}
-
-#[test]
-fn valid_combination() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 0,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.h_keys[1][0].value = 1;
-    b.h_keys[2][0].value = 1;
-    b.h_keys[10][0].value = 1;
-    b.h_keys[11][0].value = 1;
-    b.h_keys[12][0].value = 1;
-    b.size = vec![1, 15];
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Unknown
-            };
-            15
-        ];
-        1
-    ];
-    b.grid[0][6].cell_state = Status::Empty;
-    b.grid[0][1].cell_state = Status::Marked;
-    b.grid[0][11].cell_state = Status::Marked;
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    b.definite_within_bounds_v();
-    b.definite_within_bounds_h();
-    b.tighten_bounds_v();
-    b.tighten_bounds_h();
-    b.determine_spaces_between_keys_v();
-    b.determine_spaces_between_keys_h();
-    assert_eq!(b.valid_grid(), true);
-}
-
-#[test]
-fn not_valid_combination() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 0,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.h_keys[1][0].value = 1;
-    b.h_keys[2][0].value = 1;
-    b.h_keys[10][0].value = 1;
-    b.h_keys[11][0].value = 1;
-    b.h_keys[12][0].value = 1;
-    b.size = vec![1, 15];
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Unknown
-            };
-            15
-        ];
-        1
-    ];
-    b.grid[0][6].cell_state = Status::Empty;
-    b.grid[0][1].cell_state = Status::Marked;
-    b.grid[0][11].cell_state = Status::Marked;
-
-    b.grid[0][14].cell_state = Status::Marked;
-
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    b.definite_within_bounds_v();
-    b.definite_within_bounds_h();
-    b.tighten_bounds_v();
-    b.tighten_bounds_h();
-    b.determine_spaces_between_keys_v();
-    b.determine_spaces_between_keys_h();
-    assert_eq!(b.valid_grid(), false);
-}
-
-#[test]
-fn unknown_cell_test() {
-    let mut b: Board = Default::default();
-    let v: Vec<NonoKey> = vec![
-        NonoKey {
-            value: 2,
-            upper_bound: 0,
-            lower_bound: 5,
-        },
-        NonoKey {
-            value: 3,
-            upper_bound: 7,
-            lower_bound: 14,
-        },
-    ];
-    b.v_keys.push(v);
-    let v2 = vec![
-        vec![
-            NonoKey {
-                value: 0,
-                upper_bound: 0,
-                lower_bound: 0
-            };
-            1
-        ];
-        15
-    ];
-    b.h_keys = v2;
-    b.h_keys[1][0].value = 1;
-    b.h_keys[2][0].value = 1;
-    b.h_keys[10][0].value = 1;
-    b.h_keys[11][0].value = 1;
-    b.h_keys[12][0].value = 1;
-    b.size = vec![1, 15];
-    b.grid = vec![
-        vec![
-            Point {
-                cell_state: Status::Unknown
-            };
-            15
-        ];
-        1
-    ];
-    b.grid[0][6].cell_state = Status::Empty;
-    b.grid[0][1].cell_state = Status::Marked;
-    b.grid[0][11].cell_state = Status::Marked;
-    b.grid[0][0].cell_state = Status::Empty;
-    b.grid[0][14].cell_state = Status::Empty;
-    b.determine_bounds_v();
-    b.determine_bounds_h();
-    b.definite_within_bounds_v();
-    b.definite_within_bounds_h();
-    b.tighten_bounds_v();
-    b.tighten_bounds_h();
-    b.determine_spaces_between_keys_v();
-    b.determine_spaces_between_keys_h();
-    let (i, j) = b.get_unknown_cell();
-    assert_eq!(b.grid[i][j].cell_state, Status::Unknown);
+    stdout.flush().unwrap();
 }
@@ -0,0 +1,191 @@
+use crate::board::Board;
+use crate::solver::{backtrack, Search, SolveOptions, SolveStats};
+use crate::trace::SolveTrace;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+//subtrees handed out per thread, so a thread that finishes early picks up more work
+const SUBTREES_PER_THREAD: usize = 4;
+
+//Searches a board that deduction has stalled on with options.threads threads. The top of the
+//search tree is expanded into subtrees, kept in the order the sequential search visits them,
+//and each thread searches the next subtree not taken yet. Once the subtrees up to some point
+//hold limit solutions between them, the ones after it are stopped, and the solutions are
+//collected in subtree order, so the result is the one the sequential search gives.
+pub(crate) fn backtrack_parallel(
+    b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    limit: usize,
+) -> Search {
+    let root = b.snapshot();
+    let subtrees = split(b, options, stats, options.threads * SUBTREES_PER_THREAD);
+    let count = subtrees.len();
+    let next = AtomicUsize::new(0);
+    let halts: Vec<AtomicBool> = (0..count).map(|_| AtomicBool::new(false)).collect();
+    let results: Mutex<Vec<Option<(Search, SolveStats)>>> =
+        Mutex::new((0..count).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..options.threads.min(count) {
+            scope.spawn(|| loop {
+                let k = next.fetch_add(1, Ordering::Relaxed);
+                if k >= count {
+                    break;
+                }
+                let (board, depth) = subtrees[k].clone();
+                let mut sub_stats = SolveStats::default();
+                let search = if halts[k].load(Ordering::Relaxed) {
+                    Search {
+                        solutions: Vec::new(),
+                        partial: None,
+                    }
+                } else {
+                    backtrack(
+                        board,
+                        options,
+                        &mut sub_stats,
+                        &mut SolveTrace::disabled(),
+                        limit,
+                        &halts[k],
+                    )
+                };
+                if sub_stats.guesses > 0 {
+                    sub_stats.max_depth += depth;
+                }
+                let mut results = results.lock().unwrap();
+                results[k] = Some((search, sub_stats));
+                //stop every subtree after the first run of finished ones that is enough
+                let mut found = 0;
+                for (n, result) in results.iter().enumerate() {
+                    match result {
+                        Some((search, sub_stats)) => {
+                            found += search.solutions.len();
+                            if found >= limit || sub_stats.interrupted {
+                                for halt in &halts[n + 1..] {
+                                    halt.store(true, Ordering::Relaxed);
+                                }
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                }
+            });
+        }
+    });
+
+    let mut search = Search {
+        solutions: Vec::new(),
+        partial: None,
+    };
+    for (sub_search, sub_stats) in results.into_inner().unwrap().into_iter().flatten() {
+        add_stats(stats, &sub_stats);
+        search.solutions.extend(sub_search.solutions);
+        if search.solutions.len() >= limit {
+            search.solutions.truncate(limit);
+            break;
+        }
+        if sub_stats.interrupted {
+            stats.interrupted = true;
+            search.partial = Some(root);
+            break;
+        }
+    }
+    search
+}
+
+//Expands the search tree level by level until it has at least target open nodes, replacing
+//each node by its children so they stay in the order of the sequential search. Returns the
+//nodes along with the number of guesses that led to each.
+fn split(
+    b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    target: usize,
+) -> Vec<(Board, usize)> {
+    let mut nodes = vec![(b, 0)];
+    while nodes.len() < target {
+        let mut children = Vec::new();
+        let mut expanded = false;
+        for (mut b, depth) in nodes {
+            if !b.valid_grid() || b.complete_grid() {
+                children.push((b, depth));
+                continue;
+            }
+            //a grid that is not complete always has a cell to guess
+            let guess = match b.choose_branch(options.branching) {
+                Some(guess) => guess,
+                None => {
+                    children.push((b, depth));
+                    continue;
+                }
+            };
+            expanded = true;
+            stats.guesses += 1;
+            let (i, j) = guess.cell;
+            for &state in guess.order.iter() {
+                let mut child = b.snapshot();
+                let mut trace = SolveTrace::disabled();
+                stats.nodes += 1;
+                let propagated = child
+                    .set_and_propagate(i, j, state, stats, &mut trace)
+                    .and_then(|()| child.deduce(options.level, &options.limits, stats, &mut trace));
+                match propagated {
+                    Ok(()) => {
+                        stats.max_depth = stats.max_depth.max(depth + 1);
+                        children.push((child, depth + 1));
+                    }
+                    Err(_) => stats.backtracks += 1,
+                }
+            }
+        }
+        nodes = children;
+        if !expanded {
+            break;
+        }
+    }
+    nodes
+}
+
+//Adds the counters of a subtree search to the totals
+fn add_stats(total: &mut SolveStats, sub: &SolveStats) {
+    total.nodes += sub.nodes;
+    total.rounds += sub.rounds;
+    total.lines_solved += sub.lines_solved;
+    total.probes += sub.probes;
+    total.guesses += sub.guesses;
+    total.backtracks += sub.backtracks;
+    total.max_depth = total.max_depth.max(sub.max_depth);
+    let (fixed, add) = (&mut total.fixed, &sub.fixed);
+    fixed.definite_within_bounds += add.definite_within_bounds;
+    fixed.spaces_between_keys += add.spaces_between_keys;
+    fixed.separate_keys += add.separate_keys;
+    fixed.tighten_bounds += add.tighten_bounds;
+    fixed.complete_groups += add.complete_groups;
+    fixed.line_solver += add.line_solver;
+    fixed.probing += add.probing;
+}
+
+#[test]
+fn threads_find_the_sequential_solutions() {
+    use crate::Puzzle;
+    let puzzle = Puzzle::from_file("./src/puzzles/nono2.txt").unwrap();
+    //a 4x4 with every permutation matrix as a solution, 24 in all
+    let ones = vec![vec![1]; 4];
+    let permutations = Puzzle::new(ones.clone(), ones);
+    for puzzle in [puzzle, permutations] {
+        for limit in [1, 5, 100] {
+            let grids = |options: &SolveOptions| -> Vec<String> {
+                let (solutions, _) = puzzle.solutions_with(options, limit);
+                solutions.iter().map(|s| s.to_string()).collect()
+            };
+            let options = SolveOptions {
+                threads: 3,
+                ..Default::default()
+            };
+            assert_eq!(grids(&options), grids(&SolveOptions::default()));
+        }
+    }
+}
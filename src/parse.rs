@@ -0,0 +1,867 @@
+use crate::color::{Color, ColorClue, ColorPuzzle};
+use crate::schema::{is_json, is_toml, PuzzleDocument};
+use crate::{Puzzle, Solution};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+///Reasons a puzzle file could not be read. Lines and columns start at 1.
+#[derive(Debug)]
+pub enum ParseError {
+    ///The file could not be read at all
+    Io(io::Error),
+    ///The first line is not two positive numbers separated by a comma
+    BadDimensions {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    ///The file does not hold one clue line per column followed by one per row
+    WrongClueLineCount {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    ///A clue is missing or is not a number
+    NonNumericClue {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    ///The clues of a line need more cells than the line has
+    ClueTooLong {
+        line: usize,
+        column: usize,
+        token: String,
+        length: usize,
+    },
+    ///An entry of the palette of a colored puzzle is not symbol=#rrggbb, or repeats a symbol
+    BadPalette {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    ///A clue of a colored puzzle names a color missing from the palette
+    UnknownColor {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    ///The goal of a `.non` file is not a 0 or 1 for every cell
+    BadGoal {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    ///A JSON or TOML puzzle document does not follow the schema of [`crate::PuzzleDocument`],
+    ///the position being known for syntax errors only
+    Schema {
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+    ///A webpbn XML file is not well formed, or lacks an element the puzzle needs
+    Xml {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    ///An image is not a PNG or Netpbm file, or its pixel data is cut short
+    Image(String),
+}
+
+impl ParseError {
+    ///Line of the file the error was found on
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::Io(_) | ParseError::Image(_) => None,
+            ParseError::Schema { line, .. } => *line,
+            ParseError::BadDimensions { line, .. }
+            | ParseError::WrongClueLineCount { line, .. }
+            | ParseError::NonNumericClue { line, .. }
+            | ParseError::ClueTooLong { line, .. }
+            | ParseError::BadPalette { line, .. }
+            | ParseError::UnknownColor { line, .. }
+            | ParseError::BadGoal { line, .. }
+            | ParseError::Xml { line, .. } => Some(*line),
+        }
+    }
+
+    ///Column of the offending token within its line
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            ParseError::Io(_) | ParseError::Image(_) => None,
+            ParseError::Schema { column, .. } => *column,
+            ParseError::WrongClueLineCount { .. } => Some(1),
+            ParseError::BadDimensions { column, .. }
+            | ParseError::NonNumericClue { column, .. }
+            | ParseError::ClueTooLong { column, .. }
+            | ParseError::BadPalette { column, .. }
+            | ParseError::UnknownColor { column, .. }
+            | ParseError::BadGoal { column, .. }
+            | ParseError::Xml { column, .. } => Some(*column),
+        }
+    }
+
+    ///The text that could not be parsed
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            ParseError::Io(_)
+            | ParseError::WrongClueLineCount { .. }
+            | ParseError::Schema { .. }
+            | ParseError::Xml { .. }
+            | ParseError::Image(_) => None,
+            ParseError::BadDimensions { token, .. }
+            | ParseError::NonNumericClue { token, .. }
+            | ParseError::ClueTooLong { token, .. }
+            | ParseError::BadPalette { token, .. }
+            | ParseError::UnknownColor { token, .. }
+            | ParseError::BadGoal { token, .. } => Some(token),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Io(e) => write!(f, "{}", e),
+            ParseError::BadDimensions { token, .. } => {
+                write!(f, "bad dimension line '{}', expected width,height", token)
+            }
+            ParseError::WrongClueLineCount {
+                expected, found, ..
+            } => write!(
+                f,
+                "wrong number of clue lines, expected {} but found {}",
+                expected, found
+            ),
+            ParseError::NonNumericClue { token, .. } if token.is_empty() => {
+                write!(f, "missing clue")
+            }
+            ParseError::NonNumericClue { token, .. } => {
+                write!(f, "non-numeric clue '{}'", token)
+            }
+            ParseError::ClueTooLong { token, length, .. } => write!(
+                f,
+                "clue '{}' does not fit in a line of {} cells",
+                token, length
+            ),
+            ParseError::BadPalette { token, .. } => write!(
+                f,
+                "bad palette entry '{}', expected a new symbol=#rrggbb",
+                token
+            ),
+            ParseError::UnknownColor { token, .. } => {
+                write!(f, "clue '{}' has a color missing from the palette", token)
+            }
+            ParseError::BadGoal { token, .. } => {
+                write!(f, "bad goal '{}', expected a 0 or 1 for every cell", token)
+            }
+            ParseError::Schema { message, .. }
+            | ParseError::Xml { message, .. }
+            | ParseError::Image(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        ParseError::Io(e)
+    }
+}
+
+///Reads the nonogram in the given file, in the format of [`parse_nonogram`], the `.non`
+///format of [`parse_non`] or the JSON and TOML schema of [`PuzzleDocument`]. Files with a
+///`.non`, `.json` or `.toml` extension are read in that format, others in the one their
+///content looks like, see [`is_json`], [`is_toml`] and [`is_non`].
+pub fn read_nonogram<P: AsRef<Path>>(file: P) -> Result<Puzzle, ParseError> {
+    let text = fs::read_to_string(&file)?;
+    let extension = file.as_ref().extension().and_then(|ext| ext.to_str());
+    match extension {
+        Some("non") => parse_non(&text).map(|non| non.puzzle),
+        Some("json") => PuzzleDocument::from_json(&text)?.puzzle(),
+        Some("toml") => PuzzleDocument::from_toml(&text)?.puzzle(),
+        _ if is_json(&text) => PuzzleDocument::from_json(&text)?.puzzle(),
+        _ if is_toml(&text) => PuzzleDocument::from_toml(&text)?.puzzle(),
+        _ if is_non(&text) => parse_non(&text).map(|non| non.puzzle),
+        _ => parse_nonogram(&text),
+    }
+}
+
+///Parses a nonogram.
+///
+///The first line holds the dimensions (width,height), followed by one line of
+///comma separated clues per column, and then one line per row. Spaces around
+///clues and blank lines at the end of the file are ignored.
+pub fn parse_nonogram(text: &str) -> Result<Puzzle, ParseError> {
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let (width, height) = dimensions(lines.next())?;
+    let clue_lines = clue_lines(lines.collect(), 2, width + height)?;
+
+    let mut h_keys: Vec<Vec<usize>> = Vec::with_capacity(width);
+    let mut v_keys: Vec<Vec<usize>> = Vec::with_capacity(height);
+    for (count, (line, text)) in clue_lines.into_iter().enumerate() {
+        if count < width {
+            h_keys.push(parse_clues(line, text, height)?);
+        } else {
+            v_keys.push(parse_clues(line, text, width)?);
+        }
+    }
+    Ok(Puzzle::new(v_keys, h_keys))
+}
+
+///A puzzle read from a `.non` file, see [`parse_non`]
+#[derive(Debug, Clone)]
+pub struct NonPuzzle {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub puzzle: Puzzle,
+    ///The intended picture, if the file has a goal
+    pub goal: Option<Solution>,
+}
+
+///Returns true if the text is in the `.non` format, i.e. its first line that is not blank
+///starts with a keyword rather than the dimensions, not followed by '=' as in TOML
+pub fn is_non(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| {
+            line.starts_with(|c: char| c.is_ascii_alphabetic()) && !line.contains('=')
+        })
+}
+
+///Reads the `.non` file at the given path, see [`parse_non`]
+pub fn read_non<P: AsRef<Path>>(file: P) -> Result<NonPuzzle, ParseError> {
+    parse_non(&fs::read_to_string(file)?)
+}
+
+///Parses a puzzle in the `.non` format used by other solvers.
+///
+///Each line starts with a keyword: `width` and `height` followed by a number, `title` and `by`
+///followed by a (quoted) text, and `goal` followed by a quoted 0 or 1 for every cell, row by
+///row. `rows` and `columns` start a section of one line of clues per row/column, top to
+///bottom and left to right, the clues separated by commas or spaces and ended by a blank
+///line. Other keywords, such as `catalogue` or `copyright`, are ignored.
+pub fn parse_non(text: &str) -> Result<NonPuzzle, ParseError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line))
+        .peekable();
+    let mut size = [None, None];
+    let (mut title, mut author) = (None, None);
+    let mut sections: [Option<Section>; 2] = [None, None];
+    let mut goal = None;
+    while let Some((line, text)) = lines.next() {
+        let trimmed = text.trim_start();
+        let indent = text.len() - trimmed.len();
+        let (keyword, rest) =
+            trimmed.split_at(trimmed.find(char::is_whitespace).unwrap_or(trimmed.len()));
+        let column = text[..indent + keyword.len()].chars().count()
+            + 1
+            + rest.chars().take_while(|c| c.is_whitespace()).count();
+        let rest = rest.trim();
+        match keyword {
+            "width" | "height" => {
+                size[(keyword == "height") as usize] = match rest.parse() {
+                    Ok(n) if n > 0 => Some(n),
+                    _ => {
+                        return Err(ParseError::BadDimensions {
+                            line,
+                            column,
+                            token: rest.to_string(),
+                        })
+                    }
+                }
+            }
+            "title" => title = Some(unquote(rest).to_string()),
+            "by" => author = Some(unquote(rest).to_string()),
+            "goal" => goal = Some((line, column, unquote(rest))),
+            "rows" | "columns" => {
+                let mut clue_lines = Vec::new();
+                while let Some(&(line, text)) = lines.peek() {
+                    let trimmed = text.trim();
+                    if trimmed.is_empty() && clue_lines.is_empty() {
+                        lines.next();
+                    } else if trimmed.starts_with(|c: char| c.is_ascii_digit()) {
+                        clue_lines.push((line, text));
+                        lines.next();
+                    } else {
+                        break;
+                    }
+                }
+                sections[(keyword == "columns") as usize] = Some((line, clue_lines));
+            }
+            _ => {}
+        }
+    }
+    let (width, height) = match size {
+        [Some(width), Some(height)] => (width, height),
+        _ => {
+            return Err(ParseError::BadDimensions {
+                line: 1,
+                column: 1,
+                token: String::new(),
+            })
+        }
+    };
+
+    let [rows, columns] = sections;
+    let mut clues: Vec<Vec<Vec<usize>>> = Vec::with_capacity(2);
+    for (section, expected, length) in [(rows, height, width), (columns, width, height)] {
+        let (start, clue_lines) = section.unwrap_or((1, Vec::new()));
+        if clue_lines.len() != expected {
+            return Err(ParseError::WrongClueLineCount {
+                line: clue_lines
+                    .get(expected)
+                    .map_or(start + clue_lines.len() + 1, |&(line, _)| line),
+                expected,
+                found: clue_lines.len(),
+            });
+        }
+        let mut parsed = Vec::with_capacity(expected);
+        for (line, text) in clue_lines {
+            let spaced = tokens_by(text, |c| c == ',' || c.is_whitespace())
+                .into_iter()
+                .filter(|(_, token)| !token.is_empty())
+                .collect();
+            parsed.push(clue_numbers(line, text, spaced, length)?);
+        }
+        clues.push(parsed);
+    }
+    let columns = clues.pop().unwrap_or_default();
+    let rows = clues.pop().unwrap_or_default();
+    let puzzle = Puzzle::new(rows, columns);
+
+    let goal = match goal {
+        Some((line, column, cells)) => {
+            let bad = || ParseError::BadGoal {
+                line,
+                column,
+                token: cells.to_string(),
+            };
+            let cells: Vec<char> = cells.chars().filter(|c| !c.is_whitespace()).collect();
+            if cells.len() != width * height || cells.iter().any(|c| !"01".contains(*c)) {
+                return Err(bad());
+            }
+            let picture: Vec<Vec<bool>> = cells
+                .chunks(width)
+                .map(|row| row.iter().map(|&c| c == '1').collect())
+                .collect();
+            Some(Solution::from_picture(&puzzle, &picture))
+        }
+        None => None,
+    };
+    Ok(NonPuzzle {
+        title,
+        author,
+        puzzle,
+        goal,
+    })
+}
+
+//The line of a rows or columns keyword, followed by the numbered clue lines of its section
+type Section<'a> = (usize, Vec<(usize, &'a str)>);
+
+//Strips the quotes around a text, if it has them
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+///Returns true if the text is a colored nonogram, i.e. its second line is a palette
+pub fn is_color_nonogram(text: &str) -> bool {
+    text.lines()
+        .nth(1)
+        .is_some_and(|line| line.trim_start().starts_with(PALETTE))
+}
+
+///Reads the colored nonogram in the given file, see [`parse_color_nonogram`] for the format
+pub fn read_color_nonogram<P: AsRef<Path>>(file: P) -> Result<ColorPuzzle, ParseError> {
+    parse_color_nonogram(&fs::read_to_string(file)?)
+}
+
+///Parses a colored nonogram.
+///
+///The format is the one of [`parse_nonogram`] with a palette on the second line:
+///`colors:` followed by comma separated entries of a symbol, '=' and the color as
+///`#rrggbb`, optionally followed by a name, e.g. `colors: r=#ff0000 red, g=#00a000`.
+///Each clue is a length followed by the symbol of its color, like `3r`, and a clue
+///without a symbol has the first color of the palette.
+pub fn parse_color_nonogram(text: &str) -> Result<ColorPuzzle, ParseError> {
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+    let (width, height) = dimensions(lines.next())?;
+    let palette = match lines.next() {
+        Some((line, text)) => parse_palette(line, text)?,
+        None => {
+            return Err(ParseError::BadPalette {
+                line: 2,
+                column: 1,
+                token: String::new(),
+            })
+        }
+    };
+    let clue_lines = clue_lines(lines.collect(), 3, width + height)?;
+
+    let mut columns = Vec::with_capacity(width);
+    let mut rows = Vec::with_capacity(height);
+    for (count, (line, text)) in clue_lines.into_iter().enumerate() {
+        if count < width {
+            columns.push(parse_color_clues(line, text, height, &palette)?);
+        } else {
+            rows.push(parse_color_clues(line, text, width, &palette)?);
+        }
+    }
+    Ok(ColorPuzzle::new(palette, rows, columns))
+}
+
+const PALETTE: &str = "colors:";
+
+//Parses the dimension line, if there is one
+fn dimensions(first: Option<(usize, &str)>) -> Result<(usize, usize), ParseError> {
+    match first {
+        Some((line, text)) => parse_dimensions(line, text),
+        None => Err(ParseError::BadDimensions {
+            line: 1,
+            column: 1,
+            token: String::new(),
+        }),
+    }
+}
+
+//Checks that the lines after the header, the first of them being line number first, hold
+//exactly expected clue lines followed only by blank lines, and returns the clue lines
+fn clue_lines(
+    lines: Vec<(usize, &str)>,
+    first: usize,
+    expected: usize,
+) -> Result<Vec<(usize, &str)>, ParseError> {
+    let found = lines
+        .iter()
+        .rposition(|(_, text)| !text.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    if found != expected {
+        return Err(ParseError::WrongClueLineCount {
+            line: if found > expected {
+                lines[expected].0
+            } else {
+                found + first
+            },
+            expected,
+            found,
+        });
+    }
+    Ok(lines.into_iter().take(found).collect())
+}
+
+//Splits a line on commas, returning each trimmed token with its 1-based column
+fn tokens(text: &str) -> Vec<(usize, &str)> {
+    tokens_by(text, |c| c == ',')
+}
+
+//Splits a line on the separators, returning each trimmed token with its 1-based column
+fn tokens_by(text: &str, separator: impl Fn(char) -> bool) -> Vec<(usize, &str)> {
+    let mut column = 1;
+    text.split(separator)
+        .map(|raw| {
+            let start = column + raw.chars().take_while(|c| c.is_whitespace()).count();
+            column += raw.chars().count() + 1;
+            (start, raw.trim())
+        })
+        .collect()
+}
+
+fn parse_dimensions(line: usize, text: &str) -> Result<(usize, usize), ParseError> {
+    let tokens = tokens(text);
+    if tokens.len() != 2 {
+        return Err(ParseError::BadDimensions {
+            line,
+            column: 1,
+            token: text.to_string(),
+        });
+    }
+    let mut size = [0usize; 2];
+    for (i, (column, token)) in tokens.into_iter().enumerate() {
+        size[i] = match token.parse() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                return Err(ParseError::BadDimensions {
+                    line,
+                    column,
+                    token: token.to_string(),
+                })
+            }
+        };
+    }
+    //a clue line for each column and row, a count that must itself fit
+    if size[0].checked_add(size[1]).is_none() {
+        return Err(ParseError::BadDimensions {
+            line,
+            column: 1,
+            token: text.to_string(),
+        });
+    }
+    Ok((size[0], size[1]))
+}
+
+fn parse_clues(line: usize, text: &str, length: usize) -> Result<Vec<usize>, ParseError> {
+    clue_numbers(line, text, tokens(text), length)
+}
+
+//Parses the tokens of a line of clues, checking that they fit in length cells
+fn clue_numbers(
+    line: usize,
+    text: &str,
+    tokens: Vec<(usize, &str)>,
+    length: usize,
+) -> Result<Vec<usize>, ParseError> {
+    let first = tokens.first().map_or(1, |&(column, _)| column);
+    let mut clues = Vec::new();
+    for (column, token) in tokens {
+        match token.parse() {
+            Ok(n) => clues.push(n),
+            Err(_) => {
+                return Err(ParseError::NonNumericClue {
+                    line,
+                    column,
+                    token: token.to_string(),
+                })
+            }
+        }
+    }
+    //each block needs its cells and a gap, checked so that huge clues cannot overflow
+    let needed = clues.iter().try_fold(0usize, |sum, &n| match n {
+        0 => Some(sum),
+        _ => sum.checked_add(n)?.checked_add(1),
+    });
+    if needed.is_none_or(|needed| needed > length + 1) {
+        return Err(ParseError::ClueTooLong {
+            line,
+            column: first,
+            token: text.trim().to_string(),
+            length,
+        });
+    }
+    Ok(clues)
+}
+
+fn parse_palette(line: usize, text: &str) -> Result<Vec<Color>, ParseError> {
+    let start = text.find(PALETTE).map_or(0, |p| p + PALETTE.len());
+    let offset = text[..start].chars().count();
+    let mut palette: Vec<Color> = Vec::new();
+    for (column, token) in tokens(&text[start..]) {
+        let bad = || ParseError::BadPalette {
+            line,
+            column: column + offset,
+            token: token.to_string(),
+        };
+        let (symbol, rest) = token.split_once('=').ok_or_else(bad)?;
+        let mut symbol_chars = symbol.trim().chars();
+        let symbol = match (symbol_chars.next(), symbol_chars.next()) {
+            (Some(c), None) if !c.is_ascii_digit() && !".#=".contains(c) => c,
+            _ => return Err(bad()),
+        };
+        let rest = rest.trim_start();
+        let (hex, name) = rest.split_at(rest.find(' ').unwrap_or(rest.len()));
+        let hex = hex
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .ok_or_else(bad)?;
+        let mut rgb = [0; 3];
+        for (i, component) in rgb.iter_mut().enumerate() {
+            *component = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| bad())?;
+        }
+        if palette.iter().any(|color| color.symbol == symbol) {
+            return Err(bad());
+        }
+        let name = name.trim();
+        palette.push(Color {
+            name: if name.is_empty() {
+                symbol.to_string()
+            } else {
+                name.to_string()
+            },
+            symbol,
+            rgb,
+        });
+    }
+    if palette.is_empty() {
+        return Err(ParseError::BadPalette {
+            line,
+            column: 1,
+            token: text.trim().to_string(),
+        });
+    }
+    Ok(palette)
+}
+
+fn parse_color_clues(
+    line: usize,
+    text: &str,
+    length: usize,
+    palette: &[Color],
+) -> Result<Vec<ColorClue>, ParseError> {
+    let mut clues = Vec::new();
+    for (column, token) in tokens(text) {
+        let digits = token
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(token.len());
+        let (number, symbol) = token.split_at(digits);
+        let length: usize = number.parse().map_err(|_| ParseError::NonNumericClue {
+            line,
+            column,
+            token: token.to_string(),
+        })?;
+        let symbol = symbol.trim();
+        let color = if symbol.is_empty() {
+            Some(0)
+        } else {
+            palette
+                .iter()
+                .position(|color| symbol.chars().eq(std::iter::once(color.symbol)))
+        };
+        let color = color.ok_or_else(|| ParseError::UnknownColor {
+            line,
+            column,
+            token: token.to_string(),
+        })?;
+        clues.push(ColorClue { length, color });
+    }
+    let nonzero: Vec<ColorClue> = clues.into_iter().filter(|c| c.length > 0).collect();
+    if crate::color::needed_cells(&nonzero) > length {
+        return Err(ParseError::ClueTooLong {
+            line,
+            column: tokens(text)[0].0,
+            token: text.trim().to_string(),
+            length,
+        });
+    }
+    Ok(nonzero)
+}
+
+#[test]
+fn parse_valid_puzzle() {
+    let p = parse_nonogram("3, 2\n1\n 2 \n0\n1,1\n2\n\n").unwrap();
+    assert_eq!(p.width(), 3);
+    assert_eq!(p.height(), 2);
+    assert_eq!(p.column_clues(), vec![vec![1], vec![2], vec![0]]);
+    assert_eq!(p.row_clues(), vec![vec![1, 1], vec![2]]);
+}
+
+#[test]
+fn bad_dimension_line() {
+    let e = parse_nonogram("3,x\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::BadDimensions {
+            line: 1,
+            column: 3,
+            ..
+        }
+    ));
+    assert_eq!(e.token(), Some("x"));
+    assert!(matches!(
+        parse_nonogram("3\n").unwrap_err(),
+        ParseError::BadDimensions { .. }
+    ));
+    assert!(matches!(
+        parse_nonogram("").unwrap_err(),
+        ParseError::BadDimensions { .. }
+    ));
+    //sizes too large to count the clue lines of
+    let e = parse_nonogram("18446744073709551615,1\n1\n").unwrap_err();
+    assert_eq!(e.token(), Some("18446744073709551615,1"));
+    assert!(matches!(
+        parse_nonogram("9223372036854775808,9223372036854775808\n").unwrap_err(),
+        ParseError::BadDimensions { line: 1, .. }
+    ));
+}
+
+#[test]
+fn wrong_number_of_clue_lines() {
+    let e = parse_nonogram("2,2\n1\n1\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::WrongClueLineCount {
+            line: 5,
+            expected: 4,
+            found: 3
+        }
+    ));
+    let e = parse_nonogram("1,1\n1\n1\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::WrongClueLineCount {
+            line: 4,
+            expected: 2,
+            found: 3
+        }
+    ));
+}
+
+#[test]
+fn non_numeric_clue() {
+    let e = parse_nonogram("2,2\n1\n1, a\n1\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::NonNumericClue {
+            line: 3,
+            column: 4,
+            ..
+        }
+    ));
+    assert_eq!(e.token(), Some("a"));
+    //trailing comma
+    let e = parse_nonogram("2,2\n1\n1,\n1\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::NonNumericClue {
+            line: 3,
+            column: 3,
+            ..
+        }
+    ));
+    assert_eq!(e.to_string(), "missing clue");
+}
+
+#[test]
+fn clue_too_long() {
+    let e = parse_nonogram("3,1\n1\n1\n1\n2,2\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::ClueTooLong {
+            line: 5,
+            length: 3,
+            ..
+        }
+    ));
+    assert_eq!(e.token(), Some("2,2"));
+    let e = parse_nonogram("1,1\n1\n18446744073709551615,1\n").unwrap_err();
+    assert!(matches!(e, ParseError::ClueTooLong { line: 3, .. }));
+}
+
+#[test]
+fn missing_file() {
+    assert!(matches!(
+        read_nonogram("./src/puzzles/missing.txt").unwrap_err(),
+        ParseError::Io(_)
+    ));
+}
+
+#[test]
+fn parse_colored_puzzle() {
+    let text = "2,2\ncolors: r=#ff0000 red, g=#00A000\n2r\n1g\n1r,1g\n1\n";
+    assert!(is_color_nonogram(text));
+    assert!(!is_color_nonogram("2,2\n1\n"));
+    let p = parse_color_nonogram(text).unwrap();
+    assert_eq!(p.palette()[0].name, "red");
+    assert_eq!(p.palette()[1].name, "g");
+    assert_eq!(p.palette()[1].rgb, [0, 160, 0]);
+    assert_eq!(
+        p.row_clues()[0],
+        vec![
+            ColorClue {
+                length: 1,
+                color: 0
+            },
+            ColorClue {
+                length: 1,
+                color: 1
+            }
+        ]
+    );
+    assert_eq!(p.solve().unwrap().to_string(), "rg\nr.\n");
+
+    let e = parse_color_nonogram("1,1\ncolors: r=ff0000\n1\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::BadPalette {
+            line: 2,
+            column: 9,
+            ..
+        }
+    ));
+    let e = parse_color_nonogram("1,1\ncolors: r=#ff0000\n1b\n1\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::UnknownColor {
+            line: 3,
+            column: 1,
+            ..
+        }
+    ));
+    //blocks of one color need a gap, of different colors they do not
+    assert!(parse_color_nonogram("2,1\ncolors: r=#ff0000, g=#00ff00\n1\n1\n1r,1g\n").is_ok());
+    assert!(matches!(
+        parse_color_nonogram("2,1\ncolors: r=#ff0000\n1\n1\n1r,1r\n").unwrap_err(),
+        ParseError::ClueTooLong { .. }
+    ));
+    //dimensions too large to count the clue lines of, before any palette or clue is read
+    let e = parse_color_nonogram("18446744073709551615,1\ncolors: r=#ff0000\n1\n").unwrap_err();
+    assert!(matches!(e, ParseError::BadDimensions { line: 1, .. }));
+}
+
+#[test]
+fn parse_non_puzzle() {
+    let p = read_non("./src/puzzles/nono3.non").unwrap();
+    assert_eq!(p.title.as_deref(), Some("Puzzle 3"));
+    assert_eq!(p.author.as_deref(), Some("nonogram_solver"));
+    let txt = read_nonogram("./src/puzzles/nono3.txt").unwrap();
+    assert_eq!(p.puzzle.row_clues(), txt.row_clues());
+    assert_eq!(p.puzzle.column_clues(), txt.column_clues());
+    let goal = p.goal.unwrap();
+    assert!(goal.differences(&txt.solve().unwrap()).is_empty());
+    //picked by extension as well as by content
+    assert_eq!(
+        read_nonogram("./src/puzzles/nono3.non")
+            .unwrap()
+            .column_clues(),
+        txt.column_clues()
+    );
+    assert!(is_non("\nwidth 2\n"));
+    assert!(!is_non("2,2\n"));
+
+    let p = parse_non("height 2\nwidth 3\nrows\n1 1\n0\n\ncolumns\n1\n0\n1\n").unwrap();
+    assert_eq!(p.puzzle.row_clues(), vec![vec![1, 1], vec![0]]);
+    assert!(p.goal.is_none());
+    let e = parse_non("width 2\nheight x\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::BadDimensions {
+            line: 2,
+            column: 8,
+            ..
+        }
+    ));
+    let e = parse_non("width 1\nheight 2\nrows\n1\n\ncolumns\n2\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::WrongClueLineCount {
+            expected: 2,
+            found: 1,
+            ..
+        }
+    ));
+    let e = parse_non("width 1\nheight 1\nrows\n1\ncolumns\n1\ngoal \"2\"\n").unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::BadGoal {
+            line: 7,
+            column: 6,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,174 @@
+use crate::board::{Board, Status};
+use crate::grid::Grid;
+use crate::propagate::WorkQueue;
+use crate::solver::{Limits, SolveStats};
+use crate::trace::SolveTrace;
+use crate::Line;
+
+impl Board {
+    //Tries both states of every unknown cell, propagating each. A state that leads to a
+    //contradiction fixes the cell to the other one, and cells both states agree on are fixed
+    //as well. Repeats until a pass over the grid fixes nothing, returning the number of passes
+    //that fixed a cell, or the contradicting row/column if a cell fails in both states.
+    //Once the deadline passes or the search is cancelled, the passes so far are returned with
+    //the grid only partly probed, and the search reports the timeout at its next limit check.
+    pub(crate) fn probe(
+        &mut self,
+        limits: &Limits,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<usize, Line> {
+        let (height, width) = (self.size[0], self.size[1]);
+        let mut passes = 0;
+        loop {
+            let mut progress = false;
+            for i in 0..height {
+                for j in 0..width {
+                    if self.grid.get(i, j) != Status::Unknown {
+                        continue;
+                    }
+                    if limits.expired() {
+                        return Ok(passes);
+                    }
+                    stats.probes += 1;
+                    let branch = |b: &mut Board, state: Status| {
+                        let checkpoint = b.grid.checkpoint();
+                        let result = b
+                            .set_and_propagate(
+                                i,
+                                j,
+                                state,
+                                &mut SolveStats::default(),
+                                &mut SolveTrace::disabled(),
+                            )
+                            .map(|()| b.grid.snapshot());
+                        b.grid.undo(checkpoint);
+                        result
+                    };
+                    let marked = branch(self, Status::Marked);
+                    let empty = branch(self, Status::Empty);
+                    match (marked, empty) {
+                        (Err(line), Err(_)) => return Err(line),
+                        (Err(_), Ok(_)) => self.probe_forces(i, j, Status::Empty, stats, trace)?,
+                        (Ok(_), Err(_)) => self.probe_forces(i, j, Status::Marked, stats, trace)?,
+                        (Ok(marked), Ok(empty)) => {
+                            if !self.probe_agrees(i, j, &marked, &empty, stats, trace)? {
+                                continue;
+                            }
+                        }
+                    }
+                    progress = true;
+                }
+            }
+            if !progress {
+                return Ok(passes);
+            }
+            passes += 1;
+        }
+    }
+
+    //Sets a cell whose other state contradicts and propagates it
+    fn probe_forces(
+        &mut self,
+        i: usize,
+        j: usize,
+        state: Status,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<(), Line> {
+        stats.fixed.probing += 1;
+        trace.probed(i, j, Some(state), vec![(i, j)]);
+        self.set_and_propagate(i, j, state, stats, trace)
+    }
+
+    //Sets the unknown cells that the two states of cell (i, j) agree on and propagates them.
+    //Returns false if there are none.
+    fn probe_agrees(
+        &mut self,
+        i: usize,
+        j: usize,
+        marked: &Grid,
+        empty: &Grid,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<bool, Line> {
+        let mut queue = WorkQueue::new(self.size[0], self.size[1]);
+        let agreed = self.grid.agreeing_cells(marked, empty);
+        for &(r, c, state) in agreed.iter() {
+            self.grid.set(r, c, state);
+            queue.push(Line::Row(r));
+            queue.push(Line::Column(c));
+        }
+        let cells: Vec<(usize, usize)> = agreed.into_iter().map(|(r, c, _)| (r, c)).collect();
+        if cells.is_empty() {
+            return Ok(false);
+        }
+        stats.fixed.probing += cells.len();
+        trace.probed(i, j, None, cells);
+        self.propagate(&mut queue, stats, trace)?;
+        Ok(true)
+    }
+}
+
+#[test]
+fn contradictions_and_agreement_fix_cells() {
+    //line solving alone stalls on this one
+    let mut b = Board::new(
+        &[vec![1], vec![1], vec![3], vec![1]],
+        &[vec![2], vec![1], vec![1], vec![1, 1]],
+    );
+    let mut stats = SolveStats::default();
+    assert!(b.solve(&mut stats, &mut SolveTrace::disabled()));
+    assert!(!b.complete_grid());
+    assert!(
+        b.probe(&Limits::default(), &mut stats, &mut SolveTrace::disabled())
+            .unwrap()
+            > 0
+    );
+    assert!(b.complete_grid());
+    assert!(stats.fixed.probing > 0);
+
+    //both states of a cell in the 2x2 are consistent and agree on nothing
+    let mut b = Board::new(&[vec![1], vec![1]], &[vec![1], vec![1]]);
+    assert_eq!(
+        b.probe(&Limits::default(), &mut stats, &mut SolveTrace::disabled()),
+        Ok(0)
+    );
+    assert_eq!(b.unknown_count(), 4);
+}
+
+#[test]
+fn probing_stops_at_the_deadline() {
+    let mut b = Board::new(
+        &[vec![1], vec![1], vec![3], vec![1]],
+        &[vec![2], vec![1], vec![1], vec![1, 1]],
+    );
+    let mut stats = SolveStats::default();
+    assert!(b.solve(&mut stats, &mut SolveTrace::disabled()));
+    let unknown = b.unknown_count();
+    let limits = Limits {
+        deadline: Some(std::time::Instant::now()),
+        ..Default::default()
+    };
+    let mut stats = SolveStats::default();
+    assert_eq!(
+        b.probe(&limits, &mut stats, &mut SolveTrace::disabled()),
+        Ok(0)
+    );
+    assert_eq!((b.unknown_count(), stats.probes), (unknown, 0));
+
+    //the search then reports the timeout with the cells line solving found
+    let puzzle = crate::Puzzle::new(
+        vec![vec![1], vec![1], vec![3], vec![1]],
+        vec![vec![2], vec![1], vec![1], vec![1, 1]],
+    );
+    let options = crate::SolveOptions {
+        level: crate::Level::Probing,
+        limits,
+        ..Default::default()
+    };
+    match puzzle.solve_outcome(&options).0 {
+        crate::SolveOutcome::Timeout(partial) => assert!(partial.to_string().contains('?')),
+        outcome => panic!("expected a timeout, got {:?}", outcome),
+    }
+}
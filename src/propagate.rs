@@ -0,0 +1,178 @@
+use crate::board::{Board, Status};
+use crate::line::solve_line;
+use crate::solver::SolveStats;
+use crate::trace::SolveTrace;
+use crate::Line;
+use std::collections::VecDeque;
+
+//Rows and columns waiting to be solved again, each queued at most once
+pub(crate) struct WorkQueue {
+    queue: VecDeque<Line>,
+    queued_rows: Vec<bool>,
+    queued_columns: Vec<bool>,
+}
+
+impl WorkQueue {
+    pub(crate) fn new(height: usize, width: usize) -> WorkQueue {
+        WorkQueue {
+            queue: VecDeque::new(),
+            queued_rows: vec![false; height],
+            queued_columns: vec![false; width],
+        }
+    }
+
+    //Queue every row and column of the board
+    pub(crate) fn all(height: usize, width: usize) -> WorkQueue {
+        let mut queue = WorkQueue::new(height, width);
+        (0..height).for_each(|i| queue.push(Line::Row(i)));
+        (0..width).for_each(|i| queue.push(Line::Column(i)));
+        queue
+    }
+
+    pub(crate) fn push(&mut self, line: Line) {
+        let queued = match line {
+            Line::Row(i) => &mut self.queued_rows[i],
+            Line::Column(i) => &mut self.queued_columns[i],
+        };
+        if !*queued {
+            *queued = true;
+            self.queue.push_back(line);
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Line> {
+        let line = self.queue.pop_front()?;
+        match line {
+            Line::Row(i) => self.queued_rows[i] = false,
+            Line::Column(i) => self.queued_columns[i] = false,
+        }
+        Some(line)
+    }
+}
+
+impl Board {
+    //The key values and cell states of a row/column
+    pub(crate) fn line(&self, line: Line) -> (Vec<usize>, Vec<Status>) {
+        match line {
+            Line::Row(i) => (
+                self.v_keys[i].iter().map(|key| key.value).collect(),
+                self.grid.row(i),
+            ),
+            Line::Column(i) => (
+                self.h_keys[i].iter().map(|key| key.value).collect(),
+                self.grid.column(i),
+            ),
+        }
+    }
+
+    //Solves the queued lines exactly, queuing the crossing line of every cell that gets set,
+    //until the queue is empty. Stops at the first row/column that contradicts its keys.
+    pub(crate) fn propagate(
+        &mut self,
+        queue: &mut WorkQueue,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<(), Line> {
+        self.propagate_with(solve_line, queue, stats, trace)
+    }
+
+    //propagate, solving each line with the given line solver
+    pub(crate) fn propagate_with(
+        &mut self,
+        solve: fn(&[usize], &[Status]) -> Option<Vec<Status>>,
+        queue: &mut WorkQueue,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<(), Line> {
+        while let Some(line) = queue.pop() {
+            stats.lines_solved += 1;
+            let (keys, cells) = self.line(line);
+            let solved = solve(&keys, &cells).ok_or(line)?;
+            if trace.is_recording() {
+                trace.line_solved(line, &keys, &cells, &solved);
+            }
+            for (n, state) in solved.into_iter().enumerate() {
+                if state == cells[n] {
+                    continue;
+                }
+                let (i, j, crossing) = match line {
+                    Line::Row(i) => (i, n, Line::Column(n)),
+                    Line::Column(j) => (n, j, Line::Row(n)),
+                };
+                self.grid.set(i, j, state);
+                stats.fixed.line_solver += 1;
+                queue.push(crossing);
+            }
+        }
+        Ok(())
+    }
+
+    //Sets a cell and propagates the consequences through its row and column,
+    //returning the row/column found to contradict its keys if any
+    pub(crate) fn set_and_propagate(
+        &mut self,
+        i: usize,
+        j: usize,
+        state: Status,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<(), Line> {
+        self.grid.set(i, j, state);
+        let mut queue = WorkQueue::new(self.size[0], self.size[1]);
+        queue.push(Line::Row(i));
+        queue.push(Line::Column(j));
+        self.propagate(&mut queue, stats, trace)
+    }
+}
+
+#[test]
+fn only_crossing_lines_are_queued() {
+    let mut queue = WorkQueue::new(3, 3);
+    queue.push(Line::Row(1));
+    queue.push(Line::Column(2));
+    queue.push(Line::Row(1));
+    assert_eq!(queue.pop(), Some(Line::Row(1)));
+    assert_eq!(queue.pop(), Some(Line::Column(2)));
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn propagation_solves_puzzle() {
+    // #.#
+    // ###
+    // #..
+    let mut b = Board::new(
+        &[vec![1, 1], vec![3], vec![1]],
+        &[vec![3], vec![1], vec![2]],
+    );
+    let mut stats = SolveStats::default();
+    assert_eq!(
+        b.propagate(
+            &mut WorkQueue::all(3, 3),
+            &mut stats,
+            &mut SolveTrace::disabled()
+        ),
+        Ok(())
+    );
+    assert!(b.complete_grid());
+    assert_eq!(stats.fixed.line_solver, 9);
+    assert_eq!(b.grid.get(0, 1), Status::Empty);
+    assert_eq!(b.grid.get(2, 0), Status::Marked);
+}
+
+#[test]
+fn contradiction_is_reported() {
+    let mut b = Board::new(&[vec![1], vec![1]], &[vec![1], vec![1]]);
+    b.grid.set(0, 0, Status::Marked);
+    b.grid.set(1, 1, Status::Empty);
+    let mut queue = WorkQueue::new(2, 2);
+    queue.push(Line::Row(1));
+    assert_eq!(
+        b.propagate(
+            &mut queue,
+            &mut SolveStats::default(),
+            &mut SolveTrace::disabled()
+        ),
+        Err(Line::Column(0))
+    );
+}
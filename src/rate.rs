@@ -0,0 +1,208 @@
+use crate::line::overlap_line;
+use crate::propagate::WorkQueue;
+use crate::solver::{find_solutions, Limits, SolveOptions, SolveStats};
+use crate::trace::SolveTrace;
+use crate::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::fmt;
+
+///The weakest set of techniques that solves a puzzle, from easiest to hardest
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    ///Each row and column only needs the overlap of a single clue and the cells no clue reaches
+    Overlap,
+    ///Each row and column needs every placement of its clues considered together
+    LineSolving,
+    ///Some cells are only fixed by trying a state and finding a contradiction in the rows and
+    ///columns it affects
+    Probing,
+    ///The solver has to guess and backtrack
+    Backtracking,
+}
+
+impl Difficulty {
+    ///A one word name for the difficulty
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Overlap => "easy",
+            Difficulty::LineSolving => "medium",
+            Difficulty::Probing => "hard",
+            Difficulty::Backtracking => "expert",
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let techniques = match self {
+            Difficulty::Overlap => "simple line overlap",
+            Difficulty::LineSolving => "full line solving",
+            Difficulty::Probing => "probing",
+            Difficulty::Backtracking => "backtracking",
+        };
+        write!(f, "{}", techniques)
+    }
+}
+
+///How hard a puzzle is to solve, see [`rate_puzzle`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    ///Guesses made to find the solution and rule out a second one, 0 unless backtracking
+    pub guesses: usize,
+    ///Largest number of guesses in effect at once
+    pub max_depth: usize,
+    ///False if backtracking found a second solution
+    pub unique: bool,
+    ///From 0 to 100, higher is harder. Each difficulty covers a quarter of the range, and the
+    ///work needed within it places a puzzle in its quarter.
+    pub score: u32,
+}
+
+impl Rating {
+    fn new(difficulty: Difficulty, effort: usize, stats: &SolveStats, unique: bool) -> Rating {
+        let base = 25 * difficulty as u32;
+        let top = if difficulty == Difficulty::Backtracking {
+            25
+        } else {
+            24
+        };
+        Rating {
+            difficulty,
+            guesses: stats.guesses,
+            max_depth: stats.max_depth,
+            unique,
+            score: base + cmp::min(effort, top) as u32,
+        }
+    }
+
+    ///A one word name for the difficulty
+    pub fn label(&self) -> &'static str {
+        self.difficulty.label()
+    }
+}
+
+///Rates a puzzle by the weakest set of techniques that solves it, trying simple line overlap,
+///full line solving, probing and finally backtracking. Returns None if the clues are invalid or
+///no grid satisfies them.
+pub fn rate_puzzle(puzzle: &Puzzle) -> Option<Rating> {
+    rate_within(puzzle, &Limits::default(), &mut false)
+}
+
+//Rates a puzzle as rate_puzzle does, giving up once probing or the search reaches one of the
+//limits, which returns None and sets interrupted
+pub(crate) fn rate_within(
+    puzzle: &Puzzle,
+    limits: &Limits,
+    interrupted: &mut bool,
+) -> Option<Rating> {
+    puzzle.validate().ok()?;
+    let mut b = puzzle.board.clone();
+    let (height, width) = (b.size[0], b.size[1]);
+    let mut stats = SolveStats::default();
+    //how many times over every row and column was solved
+    let sweeps = |stats: &SolveStats| stats.lines_solved / (height + width);
+
+    let mut queue = WorkQueue::all(height, width);
+    b.propagate_with(
+        overlap_line,
+        &mut queue,
+        &mut stats,
+        &mut SolveTrace::disabled(),
+    )
+    .ok()?;
+    if b.complete_grid() {
+        return Some(Rating::new(
+            Difficulty::Overlap,
+            sweeps(&stats),
+            &stats,
+            true,
+        ));
+    }
+
+    let mut stats = SolveStats::default();
+    let mut queue = WorkQueue::all(height, width);
+    b.propagate(&mut queue, &mut stats, &mut SolveTrace::disabled())
+        .ok()?;
+    if b.complete_grid() {
+        return Some(Rating::new(
+            Difficulty::LineSolving,
+            sweeps(&stats),
+            &stats,
+            true,
+        ));
+    }
+
+    let passes = b
+        .probe(
+            limits,
+            &mut SolveStats::default(),
+            &mut SolveTrace::disabled(),
+        )
+        .ok()?;
+    if b.complete_grid() {
+        let stats = SolveStats::default();
+        return Some(Rating::new(Difficulty::Probing, 4 * passes, &stats, true));
+    }
+
+    //probing cut short by the limits leaves more to the search, which then stops right away
+    let mut stats = SolveStats::default();
+    let options = SolveOptions {
+        limits: limits.clone(),
+        ..Default::default()
+    };
+    let solutions = find_solutions(b, &options, &mut stats, 2).solutions;
+    if stats.interrupted {
+        *interrupted = true;
+        return None;
+    }
+    if solutions.is_empty() {
+        return None;
+    }
+    //each level of guessing weighs more than doubling the number of guesses
+    let effort = 3 * stats.max_depth + (stats.guesses + 1).ilog2() as usize;
+    Some(Rating::new(
+        Difficulty::Backtracking,
+        effort,
+        &stats,
+        solutions.len() == 1,
+    ))
+}
+
+#[test]
+fn techniques_are_graded() {
+    //a full row and column cross in the middle, overlap alone finds it
+    let plus = Puzzle::new(
+        vec![vec![1], vec![3], vec![1]],
+        vec![vec![1], vec![3], vec![1]],
+    );
+    assert_eq!(rate_puzzle(&plus).unwrap().difficulty, Difficulty::Overlap);
+
+    let rating = rate_puzzle(&Puzzle::from_file("./src/puzzles/nono3.txt").unwrap()).unwrap();
+    assert!(rating.difficulty <= Difficulty::LineSolving);
+    assert!(rating.unique);
+    assert!(rating.score < 50);
+
+    //line solving stalls here, trying the states of the remaining cells finishes it
+    let probing = Puzzle::new(
+        vec![vec![1], vec![1], vec![3], vec![1]],
+        vec![vec![2], vec![1], vec![1], vec![1, 1]],
+    );
+    let rating = rate_puzzle(&probing).unwrap();
+    assert_eq!(rating.difficulty, Difficulty::Probing);
+    assert_eq!(rating.guesses, 0);
+    assert!(rating.score >= 50 && rating.score < 75);
+
+    let ambiguous = Puzzle::new(vec![vec![1], vec![1]], vec![vec![1], vec![1]]);
+    let rating = rate_puzzle(&ambiguous).unwrap();
+    assert_eq!(rating.difficulty, Difficulty::Backtracking);
+    assert_eq!(rating.label(), "expert");
+    assert!(!rating.unique);
+    assert!(rating.guesses > 0);
+    assert!(rating.score >= 75);
+
+    let unsolvable = Puzzle::new(vec![vec![2], vec![0]], vec![vec![0], vec![1]]);
+    assert_eq!(rate_puzzle(&unsolvable), None);
+}
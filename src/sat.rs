@@ -0,0 +1,259 @@
+use crate::board::{Board, Status};
+use crate::solver::{Limits, Search, SolveOptions, SolveStats};
+use batsat::{lbool, Callbacks, Lit, SolverInterface, SolverOpts, Var};
+use std::cell::Cell;
+
+//Stops the SAT solver once the limits are reached. The solver asks before every decision, and
+//at a few other points, so counting the questions bounds the decisions for max_nodes.
+struct Stop {
+    limits: Limits,
+    asked: Cell<usize>,
+}
+
+impl Callbacks for Stop {
+    fn stop(&self) -> bool {
+        let asked = self.asked.get();
+        self.asked.set(asked + 1);
+        self.limits.reached(asked)
+    }
+}
+
+type Solver = batsat::Solver<Stop>;
+
+fn new_solver(limits: &Limits) -> Solver {
+    let stop = Stop {
+        limits: limits.clone(),
+        asked: Cell::new(0),
+    };
+    Solver::new(SolverOpts::default(), stop)
+}
+
+fn new_var(solver: &mut Solver) -> usize {
+    solver.new_var_default().idx() as usize
+}
+
+fn positive(var: usize) -> Lit {
+    Lit::new(Var::unsafe_from_idx(var as u32), true)
+}
+
+fn negative(var: usize) -> Lit {
+    !positive(var)
+}
+
+fn add_clause(solver: &mut Solver, lits: &[Lit]) {
+    solver.add_clause_reuse(&mut lits.to_vec());
+}
+
+fn model_value(solver: &Solver, var: usize) -> bool {
+    solver.value_var(Var::unsafe_from_idx(var as u32)) == lbool::TRUE
+}
+
+//Encodes the clues of the board as a CNF formula with one variable per cell, cell (i, j)
+//being variable i * width + j. Cells already known become unit clauses.
+fn encode(b: &Board, limits: &Limits) -> Solver {
+    let (height, width) = (b.size[0], b.size[1]);
+    let mut solver = new_solver(limits);
+    for _ in 0..height * width {
+        new_var(&mut solver);
+    }
+    let clues = |keys: &[crate::board::NonoKey]| -> Vec<usize> {
+        keys.iter().map(|k| k.value).filter(|&v| v > 0).collect()
+    };
+    for i in 0..height {
+        let cells: Vec<usize> = (0..width).map(|j| i * width + j).collect();
+        encode_line(&mut solver, &clues(&b.v_keys[i]), &cells);
+    }
+    for j in 0..width {
+        let cells: Vec<usize> = (0..height).map(|i| i * width + j).collect();
+        encode_line(&mut solver, &clues(&b.h_keys[j]), &cells);
+    }
+    for i in 0..height {
+        for j in 0..width {
+            match b.grid.get(i, j) {
+                Status::Marked => add_clause(&mut solver, &[positive(i * width + j)]),
+                Status::Empty => add_clause(&mut solver, &[negative(i * width + j)]),
+                Status::Unknown => {}
+            }
+        }
+    }
+    solver
+}
+
+//Adds the clauses of a single row or column. Each clue gets a variable for every cell it can
+//start at: exactly one of them is true, a clue starting at p is followed by the next clue
+//starting past p + its length, and a cell is marked exactly when a clue covers it.
+fn encode_line(solver: &mut Solver, clues: &[usize], cells: &[usize]) {
+    let total = clues.iter().sum::<usize>() + clues.len().saturating_sub(1);
+    if total > cells.len() {
+        add_clause(solver, &[]);
+        return;
+    }
+    let slack = cells.len() - total;
+    let mut first = 0;
+    //starts[t][s] is the variable of clue t starting at cell first_t + s
+    let mut starts: Vec<(usize, Vec<usize>)> = Vec::new();
+    for &clue in clues {
+        let vars: Vec<usize> = (0..=slack).map(|_| new_var(solver)).collect();
+        starts.push((first, vars));
+        first += clue + 1;
+    }
+    let mut covering: Vec<Vec<Lit>> = vec![Vec::new(); cells.len()];
+    for (t, (first, vars)) in starts.iter().enumerate() {
+        let clue = clues[t];
+        add_clause(
+            solver,
+            &vars.iter().map(|&v| positive(v)).collect::<Vec<Lit>>(),
+        );
+        for a in 0..vars.len() {
+            for b in a + 1..vars.len() {
+                add_clause(solver, &[negative(vars[a]), negative(vars[b])]);
+            }
+        }
+        for (s, &v) in vars.iter().enumerate() {
+            let p = first + s;
+            if let Some((next_first, next_vars)) = starts.get(t + 1) {
+                //the next clue starts at p + clue + 1 or later
+                let mut clause = vec![negative(v)];
+                let earliest = p + clue + 1 - next_first;
+                clause.extend(next_vars[earliest..].iter().map(|&n| positive(n)));
+                add_clause(solver, &clause);
+            }
+            for x in p..p + clue {
+                add_clause(solver, &[negative(v), positive(cells[x])]);
+                covering[x].push(positive(v));
+            }
+        }
+    }
+    for (x, covers) in covering.into_iter().enumerate() {
+        let mut clause = vec![negative(cells[x])];
+        clause.extend(covers);
+        add_clause(solver, &clause);
+    }
+}
+
+//Solves the board with a SAT solver instead of the backtracking search, returning up to limit
+//solutions. Each solution found is blocked by a clause ruling out its cells, so the next call
+//finds a different one or proves there is none. The board is left as the partial solution
+//if the limits stop the solver.
+pub(crate) fn solve_sat(
+    b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    limit: usize,
+) -> Search {
+    let mut search = Search {
+        solutions: Vec::new(),
+        partial: None,
+    };
+    let (height, width) = (b.size[0], b.size[1]);
+    let mut solver = encode(&b, &options.limits);
+    while search.solutions.len() < limit {
+        let result = solver.solve_limited(&[]);
+        if result == lbool::FALSE {
+            break;
+        }
+        if result != lbool::TRUE {
+            stats.interrupted = true;
+            search.partial = Some(b.snapshot());
+            break;
+        }
+        let mut solution = b.snapshot();
+        let mut block = Vec::with_capacity(height * width);
+        for i in 0..height {
+            for j in 0..width {
+                let v = i * width + j;
+                if model_value(&solver, v) {
+                    solution.grid.set(i, j, Status::Marked);
+                    block.push(negative(v));
+                } else {
+                    solution.grid.set(i, j, Status::Empty);
+                    block.push(positive(v));
+                }
+            }
+        }
+        solution.grid.clear_trail();
+        search.solutions.push(solution);
+        add_clause(&mut solver, &block);
+    }
+    let decisions = solver.num_decisions() as usize;
+    stats.nodes += decisions;
+    stats.guesses += decisions;
+    stats.backtracks += solver.num_conflicts() as usize;
+    search
+}
+
+#[test]
+fn clues_are_encoded() {
+    use crate::Puzzle;
+    //every placement of 2 1 in 5 cells
+    let mut solver = new_solver(&Limits::default());
+    let cells: Vec<usize> = (0..5).map(|_| new_var(&mut solver)).collect();
+    encode_line(&mut solver, &[2, 1], &cells);
+    let mut lines = Vec::new();
+    while solver.solve_limited(&[]) == lbool::TRUE {
+        let line: String = cells
+            .iter()
+            .map(|&v| if model_value(&solver, v) { '#' } else { '.' })
+            .collect();
+        let block: Vec<Lit> = cells
+            .iter()
+            .map(|&v| {
+                if model_value(&solver, v) {
+                    negative(v)
+                } else {
+                    positive(v)
+                }
+            })
+            .collect();
+        add_clause(&mut solver, &block);
+        lines.push(line);
+    }
+    lines.sort();
+    assert_eq!(lines, vec!["##.#.", "##..#", ".##.#"]);
+
+    let puzzle = Puzzle::new(vec![vec![2], vec![0]], vec![vec![1], vec![1]]);
+    let mut stats = SolveStats::default();
+    let search = solve_sat(puzzle.board, &SolveOptions::default(), &mut stats, 2);
+    assert_eq!(search.solutions.len(), 1);
+    assert_eq!(search.solutions[0].grid.get(0, 1), Status::Marked);
+    assert_eq!(search.solutions[0].grid.get(1, 0), Status::Empty);
+}
+
+#[test]
+fn sat_engine_agrees_with_search() {
+    use crate::Puzzle;
+    let ones = vec![vec![1]; 4];
+    let permutations = Puzzle::new(ones.clone(), ones);
+    let puzzle = Puzzle::from_file("./src/puzzles/nono5.txt").unwrap();
+    let sat = SolveOptions {
+        engine: crate::Engine::Sat,
+        ..Default::default()
+    };
+    for (puzzle, count) in [(permutations, 24), (puzzle, 1)] {
+        let grids = |options: &SolveOptions| -> Vec<String> {
+            let (solutions, _) = puzzle.solutions_with(options, 100);
+            let mut grids: Vec<String> = solutions.iter().map(|s| s.to_string()).collect();
+            grids.sort();
+            grids
+        };
+        let found = grids(&sat);
+        assert_eq!(found.len(), count);
+        assert_eq!(found, grids(&SolveOptions::default()));
+    }
+
+    //the limits stop the SAT solver too
+    let ones = vec![vec![1]; 8];
+    let cancelled = SolveOptions {
+        limits: Limits {
+            cancel: Some(std::sync::Arc::new(std::sync::atomic::AtomicBool::new(
+                true,
+            ))),
+            ..Default::default()
+        },
+        ..sat
+    };
+    let (solutions, partial, stats) =
+        Puzzle::new(ones.clone(), ones).solutions_outcome(&cancelled, 2);
+    assert!(solutions.is_empty() && stats.interrupted);
+    assert_eq!(partial.unwrap().to_string(), "????????\n".repeat(8));
+}
@@ -0,0 +1,403 @@
+use crate::board::Status;
+use crate::parse::ParseError;
+use crate::solver::SolveStats;
+use crate::{Puzzle, Solution};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+
+///A puzzle with its metadata, as stored in JSON and TOML puzzle files.
+///
+///In JSON:
+///```json
+///{
+///  "title": "Plus",
+///  "width": 3,
+///  "height": 3,
+///  "rows": [[1], [3], [1]],
+///  "columns": [[1], [3], [1]],
+///  "solution": [".#.", "###", ".#."],
+///  "givens": [{ "row": 1, "column": 1, "marked": true }]
+///}
+///```
+///and in TOML the same keys, with the givens as a `[[givens]]` array of tables. `title`,
+///`author`, `solution` and `givens` may be left out, and an empty row or column has `[]` or
+///`[0]` as its clues.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PuzzleDocument {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub width: usize,
+    pub height: usize,
+    ///Clues of each row, top to bottom
+    pub rows: Vec<Vec<usize>>,
+    ///Clues of each column, left to right
+    pub columns: Vec<Vec<usize>>,
+    ///The expected solution, one string per row with '#' for marked and '.' for empty cells
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Vec<String>>,
+    ///Cells whose state is given before solving, see [`Puzzle::set_given`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub givens: Vec<Given>,
+}
+
+///A cell of a [`PuzzleDocument`] filled in before solving
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Given {
+    pub row: usize,
+    pub column: usize,
+    pub marked: bool,
+}
+
+///How solving a puzzle went, see [`SolveReport`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Solved,
+    ///More than one solution was found
+    NotUnique,
+    Unsolvable,
+    Timeout,
+    ///The file could not be read or its clues are inconsistent
+    Invalid,
+}
+
+///The result of solving a puzzle, as written by `solve --format json`, one object per line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolveReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub verdict: Verdict,
+    ///Every solution found, written like [`PuzzleDocument::solution`]. After a timeout it
+    ///holds the cells found by deduction, with '?' for the unknown ones.
+    #[serde(default)]
+    pub solutions: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SolveStats>,
+}
+
+impl PuzzleDocument {
+    ///Parses a puzzle document from JSON
+    pub fn from_json(text: &str) -> Result<PuzzleDocument, ParseError> {
+        serde_json::from_str(text).map_err(|e| ParseError::Schema {
+            line: Some(e.line()).filter(|&line| line > 0),
+            column: Some(e.column()).filter(|&column| column > 0),
+            message: without_position(e.to_string()),
+        })
+    }
+
+    ///Parses a puzzle document from TOML
+    pub fn from_toml(text: &str) -> Result<PuzzleDocument, ParseError> {
+        toml::from_str(text).map_err(|e| {
+            let (line, column) = match e.span() {
+                Some(span) => {
+                    let before = &text[..span.start];
+                    let line_start = before.rfind('\n').map_or(0, |p| p + 1);
+                    (
+                        Some(before.matches('\n').count() + 1),
+                        Some(before[line_start..].chars().count() + 1),
+                    )
+                }
+                None => (None, None),
+            };
+            ParseError::Schema {
+                line,
+                column,
+                message: e.message().to_string(),
+            }
+        })
+    }
+
+    ///The document as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a puzzle document is valid JSON")
+    }
+
+    ///The document as TOML
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("a puzzle document is valid TOML")
+    }
+
+    ///Builds the puzzle, with its givens, checking that the clues and givens fit the dimensions
+    pub fn puzzle(&self) -> Result<Puzzle, ParseError> {
+        let error = |message: String| ParseError::Schema {
+            line: None,
+            column: None,
+            message,
+        };
+        if self.width == 0 || self.height == 0 {
+            return Err(error("the width and height must be positive".to_string()));
+        }
+        for (name, lines, expected, length) in [
+            ("rows", &self.rows, self.height, self.width),
+            ("columns", &self.columns, self.width, self.height),
+        ] {
+            if lines.len() != expected {
+                return Err(error(format!(
+                    "expected {} {} of clues but found {}",
+                    expected,
+                    name,
+                    lines.len()
+                )));
+            }
+            for (n, clues) in lines.iter().enumerate() {
+                let needed = clues.iter().try_fold(0usize, |sum, &c| match c {
+                    0 => Some(sum),
+                    _ => sum.checked_add(c)?.checked_add(1),
+                });
+                if needed.is_none_or(|needed| needed > length + 1) {
+                    return Err(error(format!(
+                        "the clues of {} {} do not fit in {} cells",
+                        &name[..name.len() - 1],
+                        n + 1,
+                        length
+                    )));
+                }
+            }
+        }
+        let mut puzzle = Puzzle::new(self.rows.clone(), self.columns.clone());
+        for given in self.givens.iter() {
+            if given.row >= self.height || given.column >= self.width {
+                return Err(error(format!(
+                    "given cell ({}, {}) is outside the grid",
+                    given.row, given.column
+                )));
+            }
+            let state = if given.marked {
+                Status::Marked
+            } else {
+                Status::Empty
+            };
+            puzzle.set_given(given.row, given.column, state);
+        }
+        Ok(puzzle)
+    }
+
+    ///The expected solution, if the document has one
+    pub fn expected_solution(&self) -> Result<Option<Solution>, ParseError> {
+        let rows = match &self.solution {
+            Some(rows) => rows,
+            None => return Ok(None),
+        };
+        let puzzle = self.puzzle()?;
+        let fits = rows.len() == self.height
+            && rows.iter().all(|row| {
+                row.chars().count() == self.width && row.chars().all(|c| c == '#' || c == '.')
+            });
+        if !fits {
+            return Err(ParseError::Schema {
+                line: None,
+                column: None,
+                message: format!(
+                    "the solution needs {} rows of {} '#' or '.' cells",
+                    self.height, self.width
+                ),
+            });
+        }
+        let picture: Vec<Vec<bool>> = rows
+            .iter()
+            .map(|row| row.chars().map(|c| c == '#').collect())
+            .collect();
+        Ok(Some(Solution::from_picture(&puzzle, &picture)))
+    }
+}
+
+impl From<&Puzzle> for PuzzleDocument {
+    fn from(puzzle: &Puzzle) -> PuzzleDocument {
+        PuzzleDocument {
+            title: None,
+            author: None,
+            width: puzzle.width(),
+            height: puzzle.height(),
+            rows: puzzle.row_clues(),
+            columns: puzzle.column_clues(),
+            solution: None,
+            givens: puzzle
+                .givens()
+                .into_iter()
+                .map(|(row, column, state)| Given {
+                    row,
+                    column,
+                    marked: state == Status::Marked,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<Puzzle> for PuzzleDocument {
+    fn from(puzzle: Puzzle) -> PuzzleDocument {
+        PuzzleDocument::from(&puzzle)
+    }
+}
+
+impl TryFrom<PuzzleDocument> for Puzzle {
+    type Error = ParseError;
+
+    fn try_from(document: PuzzleDocument) -> Result<Puzzle, ParseError> {
+        document.puzzle()
+    }
+}
+
+impl From<Solution> for Vec<String> {
+    fn from(solution: Solution) -> Vec<String> {
+        solution.to_string().lines().map(String::from).collect()
+    }
+}
+
+///Reads a JSON or TOML puzzle document, picking the format by the extension of the file,
+///or by its content if the extension is neither `.json` nor `.toml`
+pub fn read_document<P: AsRef<Path>>(file: P) -> Result<PuzzleDocument, ParseError> {
+    let text = fs::read_to_string(&file)?;
+    let extension = file.as_ref().extension().and_then(|ext| ext.to_str());
+    match extension {
+        Some("toml") => PuzzleDocument::from_toml(&text),
+        Some("json") => PuzzleDocument::from_json(&text),
+        _ if is_json(&text) => PuzzleDocument::from_json(&text),
+        _ => PuzzleDocument::from_toml(&text),
+    }
+}
+
+///Returns true if the text looks like a JSON object
+pub fn is_json(text: &str) -> bool {
+    text.trim_start().starts_with('{')
+}
+
+///Returns true if the text looks like TOML: its first line that is not blank or a comment
+///is a `key = value` pair or a table header
+pub fn is_toml(text: &str) -> bool {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| {
+            line.starts_with('[')
+                || line.split_once('=').is_some_and(|(key, _)| {
+                    let key = key.trim();
+                    !key.is_empty()
+                        && key
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                })
+        })
+}
+
+//serde_json ends its messages with the position, which is reported separately
+fn without_position(message: String) -> String {
+    match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message,
+    }
+}
+
+//Durations as a number of milliseconds, for SolveStats::elapsed
+pub(crate) mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub(crate) fn serialize<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(duration.as_secs_f64() * 1000.0)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        let ms = f64::deserialize(d)?.max(0.0);
+        Ok(Duration::from_nanos((ms * 1e6).round() as u64))
+    }
+}
+
+#[test]
+fn json_and_toml_round_trip() {
+    let document = PuzzleDocument {
+        title: Some("Plus".to_string()),
+        author: Some("Someone".to_string()),
+        width: 3,
+        height: 3,
+        rows: vec![vec![1], vec![3], vec![1]],
+        columns: vec![vec![1], vec![3], vec![1]],
+        solution: Some(vec![
+            ".#.".to_string(),
+            "###".to_string(),
+            ".#.".to_string(),
+        ]),
+        givens: vec![Given {
+            row: 1,
+            column: 1,
+            marked: true,
+        }],
+    };
+    assert_eq!(
+        PuzzleDocument::from_json(&document.to_json()).unwrap(),
+        document
+    );
+    assert_eq!(
+        PuzzleDocument::from_toml(&document.to_toml()).unwrap(),
+        document
+    );
+
+    let puzzle = document.puzzle().unwrap();
+    assert_eq!(puzzle.givens(), vec![(1, 1, Status::Marked)]);
+    let solution = puzzle.solve().unwrap();
+    let expected = document.expected_solution().unwrap().unwrap();
+    assert!(solution.differences(&expected).is_empty());
+    //the puzzle itself serializes to the schema without the metadata
+    let json = serde_json::to_string(&puzzle).unwrap();
+    let back: Puzzle = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.row_clues(), puzzle.row_clues());
+    assert_eq!(back.givens(), puzzle.givens());
+    assert_eq!(
+        serde_json::to_string(&solution).unwrap(),
+        "[\".#.\",\"###\",\".#.\"]"
+    );
+
+    let report = SolveReport {
+        file: Some("plus.json".to_string()),
+        verdict: Verdict::Solved,
+        solutions: vec![solution.into()],
+        stats: Some(SolveStats {
+            nodes: 3,
+            elapsed: std::time::Duration::from_micros(1500),
+            ..Default::default()
+        }),
+    };
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains("\"verdict\":\"solved\""));
+    assert!(json.contains("\"elapsed_ms\":1.5"));
+    assert_eq!(serde_json::from_str::<SolveReport>(&json).unwrap(), report);
+}
+
+#[test]
+fn bad_documents() {
+    let e = PuzzleDocument::from_json("{\n  \"width\": 2,\n  \"hieght\": 2\n}").unwrap_err();
+    assert_eq!(e.line(), Some(3));
+    assert!(e.to_string().contains("hieght"), "{}", e);
+    let e = PuzzleDocument::from_toml("width = 2\nheight = \"two\"\n").unwrap_err();
+    assert_eq!(e.line(), Some(2));
+    assert_eq!(e.column(), Some(10));
+
+    let document =
+        PuzzleDocument::from_toml("width = 2\nheight = 1\nrows = [[3]]\ncolumns = [[1], []]\n")
+            .unwrap();
+    assert!(document.puzzle().is_err());
+    let document =
+        PuzzleDocument::from_json(r#"{"width":1,"height":1,"rows":[[1]],"columns":[]}"#).unwrap();
+    assert_eq!(
+        document.puzzle().unwrap_err().to_string(),
+        "expected 1 columns of clues but found 0"
+    );
+    let document = PuzzleDocument::from_json(
+        r#"{"width":1,"height":1,"rows":[[18446744073709551615,1]],"columns":[[1]]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        document.puzzle().unwrap_err().to_string(),
+        "the clues of row 1 do not fit in 1 cells"
+    );
+    assert!(is_toml("# comment\nwidth = 2\n"));
+    assert!(!is_toml("width 2\n"));
+    assert!(is_json(" {}"));
+}
@@ -0,0 +1,604 @@
+use crate::board::{Board, Status};
+use crate::line::marked_probability;
+use crate::trace::SolveTrace;
+use crate::{Line, Solution};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+///How the backtracking search picks the next cell to guess once deduction stalls
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Branching {
+    ///The first unknown cell in row-major order, trying Empty before Marked
+    FirstUnknown,
+    ///The first unknown cell of the row or column with the fewest unknown cells
+    MostConstrainedLine,
+    ///The cell most likely to be marked according to the placement counts of its row and
+    ///column, trying Marked first
+    #[default]
+    Probability,
+    ///The cell whose guess fixes the most cells once propagated, trying the state that
+    ///fixes more first. A guess that leads to a contradiction is picked right away.
+    Probing,
+}
+
+///Bounds on how long the search may run. The search stops at the first bound reached.
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    ///Wall-clock time to give up at
+    pub deadline: Option<Instant>,
+    ///Largest number of guesses to make
+    pub max_nodes: Option<usize>,
+    ///Set to true from another thread to stop the search
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Limits {
+    //Returns true once any of the limits has been reached
+    pub(crate) fn reached(&self, nodes: usize) -> bool {
+        self.max_nodes.is_some_and(|max| nodes >= max) || self.expired()
+    }
+
+    //Returns true once the deadline has passed or the search was cancelled, the limits that
+    //also cut deduction short
+    pub(crate) fn expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+            || self
+                .cancel
+                .as_ref()
+                .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+///How much deduction is done before each guess
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Level {
+    ///The deduction rules, then every row and column solved exactly until nothing changes
+    #[default]
+    LineSolving,
+    ///Line solving, then trying both states of every unknown cell: a state that leads to a
+    ///contradiction fixes the other one, and cells both states agree on are fixed too. Slower
+    ///per node, but solves many puzzles without guessing.
+    Probing,
+}
+
+///The method used once deduction from the clues stalls
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Engine {
+    ///The backtracking search, guessing cells as picked by [`Branching`]
+    #[default]
+    Search,
+    ///Encodes the clues of every row and column as a CNF formula and hands it to a SAT
+    ///solver, often much faster on large puzzles that need a lot of guessing. Further
+    ///solutions are found by ruling out each one found. In [`SolveStats`], nodes and guesses
+    ///count the decisions of the SAT solver and backtracks its conflicts.
+    #[cfg(feature = "sat")]
+    Sat,
+}
+
+///Settings for [`crate::Puzzle::solve_with`]
+#[derive(Debug, Default, Clone)]
+pub struct SolveOptions {
+    pub engine: Engine,
+    pub branching: Branching,
+    pub level: Level,
+    pub limits: Limits,
+    ///Number of threads to search with, only used when built with the `parallel` feature.
+    ///0 and 1 search on the calling thread. With more, the search tree is split into
+    ///subtrees searched side by side, `max_nodes` bounds each subtree on its own, and the
+    ///solutions come out in the same order as when searching on one thread.
+    pub threads: usize,
+}
+
+///Counters collected while solving
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolveStats {
+    ///Number of cell states tried by the search, i.e. nodes of the search tree explored
+    pub nodes: usize,
+    ///True if the search was stopped by one of the [`Limits`] before it finished
+    pub interrupted: bool,
+    ///Passes of the deduction rules over every row and column
+    pub rounds: usize,
+    ///Rows and columns solved by the exact line solver, before and during the search
+    pub lines_solved: usize,
+    ///Cells set by each rule
+    pub fixed: FixedCells,
+    ///Cells whose two states were tried by probing
+    pub probes: usize,
+    ///Cells the search had to guess
+    pub guesses: usize,
+    ///Guesses that led to a contradiction, forcing the search to back up
+    pub backtracks: usize,
+    ///Largest number of guesses in effect at once
+    pub max_depth: usize,
+    ///Time spent solving, serialized as `elapsed_ms`
+    #[serde(rename = "elapsed_ms", with = "crate::schema::millis")]
+    pub elapsed: Duration,
+}
+
+///Number of cells set by each of the deduction rules
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FixedCells {
+    pub definite_within_bounds: usize,
+    pub spaces_between_keys: usize,
+    pub separate_keys: usize,
+    pub tighten_bounds: usize,
+    pub complete_groups: usize,
+    ///Cells set by the exact line solver once the rules stall, including those set while
+    ///propagating guesses
+    pub line_solver: usize,
+    ///Cells set by probing, not counting those its propagation sets
+    pub probing: usize,
+}
+
+///Result of solving a puzzle within [`Limits`], a [`Solution`] or for colored puzzles a
+///[`ColorSolution`](crate::ColorSolution)
+#[derive(Debug, Clone)]
+pub enum SolveOutcome<S = Solution> {
+    Solved(S),
+    ///No grid satisfies the clues
+    Unsat,
+    ///A limit was reached first. Holds the cells that follow from the clues by deduction
+    ///alone, the rest are left unknown.
+    Timeout(S),
+}
+
+//What a search found before it finished or was interrupted
+pub(crate) struct Search {
+    pub(crate) solutions: Vec<Board>,
+    //the board after deduction alone, kept when the search is interrupted
+    pub(crate) partial: Option<Board>,
+}
+
+//A guessed cell and the states to try for it, in order
+pub(crate) struct Guess {
+    pub(crate) cell: (usize, usize),
+    pub(crate) order: [Status; 2],
+    tried: usize,
+}
+
+//solves the given puzzle using deductive methods until no more deductions are possible
+//once deductive reasoning cannot be used, make a guess, check whether the guess makes for a valid solution
+//if valid, keep using deduction on the lines crossing the guess, otherwise, pop from the stack and make another guess
+pub(crate) fn find_solution(b: Board, options: &SolveOptions, stats: &mut SolveStats) -> Search {
+    find_solutions(b, options, stats, 1)
+}
+
+//keeps searching after a complete grid is found, returning up to limit distinct solutions
+pub(crate) fn find_solutions(
+    b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    limit: usize,
+) -> Search {
+    find_solutions_traced(b, options, stats, &mut SolveTrace::disabled(), limit)
+}
+
+//find_solutions, logging every deduction and guess to the trace
+pub(crate) fn find_solutions_traced(
+    mut b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    trace: &mut SolveTrace,
+    limit: usize,
+) -> Search {
+    let start = Instant::now();
+    let search = Search {
+        solutions: Vec::new(),
+        partial: None,
+    };
+    if limit == 0 || !b.solve(stats, trace) {
+        stats.elapsed = start.elapsed();
+        return search;
+    }
+    if let Err(line) = b.deduce(options.level, &options.limits, stats, trace) {
+        trace.contradiction(line);
+        stats.elapsed = start.elapsed();
+        return search;
+    }
+    #[cfg(feature = "sat")]
+    {
+        if options.engine == Engine::Sat && !trace.is_recording() {
+            let search = crate::sat::solve_sat(b, options, stats, limit);
+            stats.elapsed = start.elapsed();
+            return search;
+        }
+    }
+    #[cfg(feature = "parallel")]
+    {
+        if options.threads > 1 && !trace.is_recording() {
+            let search = crate::parallel::backtrack_parallel(b, options, stats, limit);
+            stats.elapsed = start.elapsed();
+            return search;
+        }
+    }
+    let search = backtrack(b, options, stats, trace, limit, &AtomicBool::new(false));
+    stats.elapsed = start.elapsed();
+    search
+}
+
+//Searches a board that deduction has stalled on, guessing cells and backing up on
+//contradictions. Stops early, like on reaching a limit, once halt is set.
+pub(crate) fn backtrack(
+    mut b: Board,
+    options: &SolveOptions,
+    stats: &mut SolveStats,
+    trace: &mut SolveTrace,
+    limit: usize,
+    halt: &AtomicBool,
+) -> Search {
+    let mut search = Search {
+        solutions: Vec::new(),
+        partial: None,
+    };
+    //guesses are undone by rolling back the trail of the grid, so the search works on a
+    //single board and only copies the grid of a solution or of the deductions before guessing
+    b.grid.clear_trail();
+    let root = b.grid.snapshot();
+    let mut guesses: Vec<(Guess, usize)> = Vec::new();
+    let mut descend = true;
+    loop {
+        if descend {
+            descend = false;
+            if !b.valid_grid() {
+                stats.backtracks += 1;
+            } else if b.complete_grid() {
+                search.solutions.push(b.snapshot());
+                if search.solutions.len() == limit {
+                    break;
+                }
+            } else if options.limits.reached(stats.nodes) || halt.load(Ordering::Relaxed) {
+                //limits are only checked before a guess, a puzzle solved by deduction is never
+                //interrupted
+                stats.interrupted = true;
+                let mut partial = b.snapshot();
+                partial.grid = root;
+                search.partial = Some(partial);
+                break;
+            } else if let Some(guess) = b.choose_branch(options.branching) {
+                //a grid that is not complete always has a cell to guess
+                stats.guesses += 1;
+                guesses.push((guess, b.grid.checkpoint()));
+            }
+        }
+
+        //try the next state of the latest guess, or give it up once both were tried
+        let (a_guess, checkpoint) = match guesses.last_mut() {
+            Some(last) => last,
+            None => break,
+        };
+        b.grid.undo(*checkpoint);
+        if a_guess.tried == 2 {
+            guesses.pop();
+            continue;
+        }
+        let (i, j) = a_guess.cell;
+        let state = a_guess.order[a_guess.tried];
+        a_guess.tried += 1;
+        stats.nodes += 1;
+        trace.guess(i, j, state, guesses.len());
+        let propagated = b
+            .set_and_propagate(i, j, state, stats, trace)
+            .and_then(|()| b.deduce(options.level, &options.limits, stats, trace));
+        match propagated {
+            Ok(()) => {
+                descend = true;
+                stats.max_depth = stats.max_depth.max(guesses.len());
+            }
+            Err(line) => {
+                trace.contradiction(line);
+                stats.backtracks += 1;
+            }
+        }
+    }
+    search
+}
+
+impl Board {
+    //Runs the deduction beyond line solving that the level asks for, stopping early once the
+    //limits expire
+    pub(crate) fn deduce(
+        &mut self,
+        level: Level,
+        limits: &Limits,
+        stats: &mut SolveStats,
+        trace: &mut SolveTrace,
+    ) -> Result<(), Line> {
+        match level {
+            Level::LineSolving => Ok(()),
+            Level::Probing => self.probe(limits, stats, trace).map(|_| ()),
+        }
+    }
+
+    //Picks the unknown cell to guess next and the order to try its states in, None if every
+    //cell is known
+    pub(crate) fn choose_branch(&mut self, branching: Branching) -> Option<Guess> {
+        let (cell, first) = match branching {
+            Branching::FirstUnknown => (self.get_unknown_cell()?, Status::Empty),
+            Branching::MostConstrainedLine => (self.most_constrained_cell()?, Status::Empty),
+            Branching::Probability => self.most_probable_cell()?,
+            Branching::Probing => self.best_probed_cell()?,
+        };
+        let second = match first {
+            Status::Marked => Status::Empty,
+            _ => Status::Marked,
+        };
+        Some(Guess {
+            cell,
+            order: [first, second],
+            tried: 0,
+        })
+    }
+
+    //Returns the first unknown cell of the row/column with the fewest unknown cells
+    fn most_constrained_cell(&self) -> Option<(usize, usize)> {
+        let (height, width) = (self.size[0], self.size[1]);
+        let mut best = None;
+        let mut best_count = usize::MAX;
+        let lines = (0..height)
+            .map(Line::Row)
+            .chain((0..width).map(Line::Column));
+        for line in lines {
+            let cells: Vec<(usize, usize)> = match line {
+                Line::Row(i) => (0..width).map(|j| (i, j)).collect(),
+                Line::Column(j) => (0..height).map(|i| (i, j)).collect(),
+            };
+            let unknown: Vec<(usize, usize)> = cells
+                .into_iter()
+                .filter(|&(i, j)| self.grid.get(i, j) == Status::Unknown)
+                .collect();
+            if !unknown.is_empty() && unknown.len() < best_count {
+                best_count = unknown.len();
+                best = Some(unknown[0]);
+            }
+        }
+        best
+    }
+
+    //Returns the unknown cell most likely to be marked, going by the fraction of the
+    //placements of its row keys and of its column keys that mark it
+    fn most_probable_cell(&self) -> Option<((usize, usize), Status)> {
+        let (height, width) = (self.size[0], self.size[1]);
+        let probabilities = |line: Line| {
+            let (keys, cells) = self.line(line);
+            marked_probability(&keys, &cells).unwrap_or_else(|| vec![0.5; cells.len()])
+        };
+        let rows: Vec<Vec<f64>> = (0..height).map(|i| probabilities(Line::Row(i))).collect();
+        let columns: Vec<Vec<f64>> = (0..width).map(|j| probabilities(Line::Column(j))).collect();
+
+        let mut best = None;
+        let mut best_p = -1.0;
+        for i in 0..height {
+            for j in 0..width {
+                if self.grid.get(i, j) != Status::Unknown {
+                    continue;
+                }
+                let p = rows[i][j] * columns[j][i];
+                if p > best_p {
+                    best_p = p;
+                    best = Some((i, j));
+                }
+            }
+        }
+        best.map(|cell| (cell, Status::Marked))
+    }
+
+    //Tries both states of every unknown cell, returning the cell whose weaker guess still
+    //fixes the most cells, along with the state that fixes more
+    fn best_probed_cell(&mut self) -> Option<((usize, usize), Status)> {
+        let (height, width) = (self.size[0], self.size[1]);
+        let unknown_before = self.unknown_count();
+        let fixed = |b: &mut Board, i: usize, j: usize, state: Status| {
+            let checkpoint = b.grid.checkpoint();
+            let mut stats = SolveStats::default();
+            let fixed = b
+                .set_and_propagate(i, j, state, &mut stats, &mut SolveTrace::disabled())
+                .ok()
+                .map(|()| unknown_before - b.unknown_count());
+            b.grid.undo(checkpoint);
+            fixed
+        };
+
+        let mut best = (self.get_unknown_cell()?, Status::Empty);
+        let mut best_score = 0;
+        for i in 0..height {
+            for j in 0..width {
+                if self.grid.get(i, j) != Status::Unknown {
+                    continue;
+                }
+                match (
+                    fixed(self, i, j, Status::Marked),
+                    fixed(self, i, j, Status::Empty),
+                ) {
+                    //one of the states contradicts, so the other one is forced
+                    (None, _) => return Some(((i, j), Status::Empty)),
+                    (_, None) => return Some(((i, j), Status::Marked)),
+                    (Some(marked), Some(empty)) => {
+                        let score = marked.min(empty);
+                        if score > best_score {
+                            best_score = score;
+                            let state = if marked >= empty {
+                                Status::Marked
+                            } else {
+                                Status::Empty
+                            };
+                            best = ((i, j), state);
+                        }
+                    }
+                }
+            }
+        }
+        Some(best)
+    }
+
+    //Returns the number of cells that are neither marked nor empty
+    pub(crate) fn unknown_count(&self) -> usize {
+        self.grid.unknown_count()
+    }
+}
+
+#[cfg(test)]
+fn ambiguous_board() -> Board {
+    //two solutions: the diagonal and the anti-diagonal
+    Board::new(&[vec![1], vec![1]], &[vec![1], vec![1]])
+}
+
+#[test]
+fn every_strategy_finds_a_solution() {
+    for branching in [
+        Branching::FirstUnknown,
+        Branching::MostConstrainedLine,
+        Branching::Probability,
+        Branching::Probing,
+    ] {
+        let mut stats = SolveStats::default();
+        let options = SolveOptions {
+            branching,
+            ..Default::default()
+        };
+        let solved = find_solution(ambiguous_board(), &options, &mut stats)
+            .solutions
+            .pop()
+            .unwrap();
+        assert!(solved.complete_grid());
+        assert!(solved.valid_grid());
+        assert_eq!(stats.nodes, 1);
+    }
+}
+
+#[test]
+fn first_unknown_tries_empty_first() {
+    let mut b = ambiguous_board();
+    let guess = b.choose_branch(Branching::FirstUnknown).unwrap();
+    assert_eq!(guess.cell, (0, 0));
+    assert_eq!(guess.order, [Status::Empty, Status::Marked]);
+    let options = SolveOptions {
+        branching: Branching::FirstUnknown,
+        ..Default::default()
+    };
+    let solved = find_solution(b, &options, &mut SolveStats::default())
+        .solutions
+        .pop()
+        .unwrap();
+    assert_eq!(solved.grid.get(0, 0), Status::Empty);
+    assert_eq!(solved.grid.get(0, 1), Status::Marked);
+}
+
+#[test]
+fn most_constrained_line_is_picked() {
+    let mut b = Board::new(&[vec![1], vec![1], vec![1]], &[vec![1], vec![1], vec![1]]);
+    b.grid.set(1, 0, Status::Empty);
+    b.grid.set(1, 2, Status::Empty);
+    assert_eq!(b.most_constrained_cell(), Some((1, 1)));
+}
+
+#[test]
+fn all_solutions_are_found() {
+    let mut stats = SolveStats::default();
+    let solutions =
+        find_solutions(ambiguous_board(), &SolveOptions::default(), &mut stats, 10).solutions;
+    assert_eq!(solutions.len(), 2);
+    assert_ne!(solutions[0].grid.get(0, 0), solutions[1].grid.get(0, 0));
+    let limited = find_solutions(ambiguous_board(), &SolveOptions::default(), &mut stats, 1);
+    assert_eq!(limited.solutions.len(), 1);
+}
+
+#[test]
+fn search_stops_at_limits() {
+    let mut options = SolveOptions::default();
+    options.limits.max_nodes = Some(0);
+    let mut stats = SolveStats::default();
+    let search = find_solution(ambiguous_board(), &options, &mut stats);
+    assert!(search.solutions.is_empty());
+    assert!(stats.interrupted);
+    assert_eq!(search.partial.unwrap().unknown_count(), 4);
+
+    let cancel = Arc::new(AtomicBool::new(true));
+    let mut options = SolveOptions::default();
+    options.limits.cancel = Some(cancel.clone());
+    let mut stats = SolveStats::default();
+    assert!(find_solution(ambiguous_board(), &options, &mut stats)
+        .partial
+        .is_some());
+    cancel.store(false, Ordering::Relaxed);
+    let mut stats = SolveStats::default();
+    assert_eq!(
+        find_solution(ambiguous_board(), &options, &mut stats)
+            .solutions
+            .len(),
+        1
+    );
+    assert!(!stats.interrupted);
+
+    let mut options = SolveOptions::default();
+    options.limits.deadline = Some(Instant::now());
+    let mut stats = SolveStats::default();
+    find_solution(ambiguous_board(), &options, &mut stats);
+    assert!(stats.interrupted);
+}
+
+#[test]
+fn probing_level_avoids_guesses() {
+    let b = Board::new(
+        &[vec![1], vec![1], vec![3], vec![1]],
+        &[vec![2], vec![1], vec![1], vec![1, 1]],
+    );
+    let mut stats = SolveStats::default();
+    let options = SolveOptions {
+        level: Level::Probing,
+        ..Default::default()
+    };
+    assert_eq!(
+        find_solution(b.clone(), &options, &mut stats)
+            .solutions
+            .len(),
+        1
+    );
+    assert_eq!(stats.guesses, 0);
+    assert!(stats.probes > 0);
+
+    let mut stats = SolveStats::default();
+    assert_eq!(
+        find_solution(b, &SolveOptions::default(), &mut stats)
+            .solutions
+            .len(),
+        1
+    );
+    assert!(stats.guesses > 0);
+}
+
+#[test]
+fn search_counters() {
+    let mut stats = SolveStats::default();
+    find_solutions(ambiguous_board(), &SolveOptions::default(), &mut stats, 10);
+    assert_eq!(stats.guesses, 1);
+    assert_eq!(stats.nodes, 2);
+    assert_eq!(stats.max_depth, 1);
+    assert_eq!(stats.backtracks, 0);
+
+    //every cell of a puzzle solved without guessing is fixed by some rule
+    let b = crate::read_nonogram("./src/puzzles/nono3.txt")
+        .unwrap()
+        .board;
+    let mut stats = SolveStats::default();
+    assert_eq!(
+        find_solution(b, &SolveOptions::default(), &mut stats)
+            .solutions
+            .len(),
+        1
+    );
+    let fixed = stats.fixed;
+    let total = fixed.definite_within_bounds
+        + fixed.spaces_between_keys
+        + fixed.separate_keys
+        + fixed.tighten_bounds
+        + fixed.complete_groups
+        + fixed.line_solver;
+    assert_eq!(total, 100);
+    assert_eq!(stats.guesses, 0);
+    assert!(stats.rounds > 0);
+}
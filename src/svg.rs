@@ -0,0 +1,175 @@
+use crate::board::Status;
+use crate::{Puzzle, Solution};
+
+///Settings for [`render_svg`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SvgOptions {
+    ///Side of a cell in pixels, the clue numbers are sized to fit
+    pub cell_size: usize,
+    ///Draws a thicker line after every 5 rows and columns
+    pub guides: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            cell_size: 20,
+            guides: false,
+        }
+    }
+}
+
+const THIN: &str = "#999999";
+const INK: &str = "#000000";
+const UNKNOWN: &str = "#cccccc";
+
+///Draws a puzzle as an SVG image, with the clues of each row to the left of the grid and the
+///clues of each column above it as in the terminal output. The cells of the solution are
+///filled in, unknown cells of a partial solution in grey. Without a solution the blank sheet
+///is drawn, showing only the givens of the puzzle, an empty given as a dot.
+pub fn render_svg(puzzle: &Puzzle, solution: Option<&Solution>, options: &SvgOptions) -> String {
+    let cell = options.cell_size.max(4);
+    let (vkey_max, hkey_max) = puzzle.key_dimensions();
+    let margin = cell / 2;
+    let left = margin + vkey_max * cell;
+    let top = margin + hkey_max * cell;
+    let (width, height) = (puzzle.width(), puzzle.height());
+    let total_width = left + width * cell + margin;
+    let total_height = top + height * cell + margin;
+    let mut svg = String::new();
+    let mut w = |text: String| svg.push_str(&text);
+
+    w(format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        total_width, total_height
+    ));
+    w(format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n",
+        total_width, total_height
+    ));
+
+    //clues, the last one of each line next to the grid
+    w(format!(
+        "<g font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        cell * 3 / 5
+    ));
+    for (i, clues) in puzzle.row_clues().iter().enumerate() {
+        for (k, clue) in clues.iter().rev().enumerate() {
+            let x = left - k * cell - cell / 2;
+            let y = top + i * cell + cell / 2;
+            w(format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x, y, clue));
+        }
+    }
+    for (j, clues) in puzzle.column_clues().iter().enumerate() {
+        for (k, clue) in clues.iter().rev().enumerate() {
+            let x = left + j * cell + cell / 2;
+            let y = top - k * cell - cell / 2;
+            w(format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x, y, clue));
+        }
+    }
+    w("</g>\n".to_string());
+
+    //cells
+    let cells: Vec<Vec<Status>> = match solution {
+        Some(solution) => solution.rows(),
+        None => {
+            let mut cells = vec![vec![Status::Unknown; width]; height];
+            for (i, j, state) in puzzle.givens() {
+                cells[i][j] = state;
+            }
+            cells
+        }
+    };
+    for (i, row) in cells.iter().enumerate() {
+        for (j, state) in row.iter().enumerate() {
+            let (x, y) = (left + j * cell, top + i * cell);
+            match state {
+                Status::Marked => w(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x, y, cell, cell, INK
+                )),
+                Status::Unknown if solution.is_some() => w(format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x, y, cell, cell, UNKNOWN
+                )),
+                Status::Empty if solution.is_none() => w(format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+                    x + cell / 2,
+                    y + cell / 2,
+                    (cell / 10).max(1),
+                    INK
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    //grid lines, the guides and the border over the thin ones
+    let mut lines = |color: &str, stroke: usize, pick: &dyn Fn(usize, usize) -> bool| {
+        let mut group = format!("<g stroke=\"{}\" stroke-width=\"{}\">\n", color, stroke);
+        for i in 0..=height {
+            if pick(i, height) {
+                let y = top + i * cell;
+                group.push_str(&format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+                    left,
+                    y,
+                    left + width * cell,
+                    y
+                ));
+            }
+        }
+        for j in 0..=width {
+            if pick(j, width) {
+                let x = left + j * cell;
+                group.push_str(&format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"/>\n",
+                    x,
+                    top,
+                    x,
+                    top + height * cell
+                ));
+            }
+        }
+        group.push_str("</g>\n");
+        w(group);
+    };
+    lines(THIN, 1, &|_, _| true);
+    let guides = options.guides;
+    lines(INK, 2, &|n, end| {
+        n == 0 || n == end || (guides && n % 5 == 0)
+    });
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[test]
+fn draw_clues_and_cells() {
+    // .#.
+    // ###
+    let mut p = Puzzle::new(vec![vec![1], vec![3]], vec![vec![1], vec![2], vec![1]]);
+    let solution = p.solve().unwrap();
+    let svg = render_svg(&p, Some(&solution), &SvgOptions::default());
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<text ").count(), 5);
+    //the background and the 4 marked cells
+    assert_eq!(svg.matches("<rect ").count(), 5);
+    //3 row and 4 column lines, thin and thick for the border
+    assert_eq!(svg.matches("<line ").count(), 7 + 4);
+
+    p.set_given(0, 0, Status::Empty);
+    p.set_given(1, 1, Status::Marked);
+    let sheet = render_svg(&p, None, &SvgOptions::default());
+    assert_eq!(sheet.matches("<rect ").count(), 2);
+    assert_eq!(sheet.matches("<circle ").count(), 1);
+
+    //every fifth line is a guide
+    let p = Puzzle::new(vec![vec![0]; 10], vec![vec![0]; 10]);
+    let options = SvgOptions {
+        guides: true,
+        ..Default::default()
+    };
+    let svg = render_svg(&p, None, &options);
+    assert_eq!(svg.matches("<line ").count(), 22 + 6);
+}
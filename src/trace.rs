@@ -0,0 +1,416 @@
+use crate::board::Status;
+use crate::grid::Grid;
+use crate::line::clue_spans;
+use crate::Line;
+use std::fmt;
+
+///The reasoning that fixed a group of cells in a row or column
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Rule {
+    ///Cells a clue covers wherever it sits between its bounds
+    DefiniteWithinBounds,
+    ///Cells left between clues that cannot hold any of them
+    SpacesBetweenKeys,
+    ///Cells that keep neighbouring clues apart
+    SeparateKeys,
+    ///Cells fixed after narrowing the bounds of the clues
+    TightenBounds,
+    ///Cells around a group that already has the length of its clue
+    CompleteGroups,
+    ///The clue of the given length can only cover cells first to last (from 0), so every
+    ///placement of it covers the cells in the middle
+    Overlap {
+        clue: usize,
+        first: usize,
+        last: usize,
+    },
+    ///No clue can reach the cells
+    Unreachable,
+    ///Every placement of the clues consistent with the known cells agrees on the cells
+    LineSolver,
+}
+
+///One entry of the deduction log. The depth is the number of guesses in effect, so steps at
+///depth 0 follow from the clues alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    ///Cells of a row/column, given by their position along it, set by reasoning about its clues
+    Deduction {
+        line: Line,
+        rule: Rule,
+        state: Status,
+        cells: Vec<usize>,
+        depth: usize,
+    },
+    ///The search assumed the state of a cell
+    Guess {
+        row: usize,
+        column: usize,
+        state: Status,
+        depth: usize,
+    },
+    ///Trying both states of a cell fixed some cells. If one state led to a contradiction,
+    ///forced holds the other state and the cell is the only one fixed, otherwise the cells are
+    ///those both states agree on.
+    Probe {
+        row: usize,
+        column: usize,
+        forced: Option<Status>,
+        cells: Vec<(usize, usize)>,
+        depth: usize,
+    },
+    ///The row/column can no longer satisfy its clues, so the latest guess is undone
+    Contradiction { line: Line, depth: usize },
+}
+
+impl Step {
+    ///Number of guesses in effect when the step was taken
+    pub fn depth(&self) -> usize {
+        match self {
+            Step::Deduction { depth, .. }
+            | Step::Guess { depth, .. }
+            | Step::Probe { depth, .. }
+            | Step::Contradiction { depth, .. } => *depth,
+        }
+    }
+
+    ///Returns true for a guess made by the search
+    pub fn is_guess(&self) -> bool {
+        matches!(self, Step::Guess { .. })
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Deduction {
+                line,
+                rule,
+                state,
+                cells,
+                ..
+            } => {
+                write!(f, "{}: ", line)?;
+                match rule {
+                    Rule::DefiniteWithinBounds => write!(f, "a clue held within its bounds")?,
+                    Rule::SpacesBetweenKeys => write!(f, "the gaps between clues")?,
+                    Rule::SeparateKeys => write!(f, "separating neighbouring clues")?,
+                    Rule::TightenBounds => write!(f, "tightening the bounds of the clues")?,
+                    Rule::CompleteGroups => write!(f, "completing a group")?,
+                    Rule::Overlap { clue, first, last } if first == last => {
+                        write!(f, "clue {} fitting only in cell {}", clue, first + 1)?
+                    }
+                    Rule::Overlap { clue, first, last } => write!(
+                        f,
+                        "overlap of clue {} in cells {}-{}",
+                        clue,
+                        first + 1,
+                        last + 1
+                    )?,
+                    Rule::Unreachable => write!(f, "no clue reaching")?,
+                    Rule::LineSolver => write!(f, "every placement of the clues")?,
+                }
+                let verb = if *state == Status::Marked {
+                    "marks"
+                } else {
+                    "clears"
+                };
+                let plural = if cells.len() > 1 { "s" } else { "" };
+                write!(f, " {} cell{} ", verb, plural)?;
+                write_ranges(f, cells)
+            }
+            Step::Guess {
+                row, column, state, ..
+            } => write!(
+                f,
+                "guess: row {}, column {} is {}",
+                row + 1,
+                column + 1,
+                state_name(*state)
+            ),
+            Step::Probe {
+                row,
+                column,
+                forced: Some(state),
+                ..
+            } => write!(
+                f,
+                "probing row {}, column {}: {} it leads to a contradiction, so it is {}",
+                row + 1,
+                column + 1,
+                if *state == Status::Marked {
+                    "clearing"
+                } else {
+                    "marking"
+                },
+                state_name(*state)
+            ),
+            Step::Probe {
+                row,
+                column,
+                forced: None,
+                cells,
+                ..
+            } => write!(
+                f,
+                "probing row {}, column {}: both of its states fix the same {} cell{}",
+                row + 1,
+                column + 1,
+                cells.len(),
+                if cells.len() > 1 { "s" } else { "" }
+            ),
+            Step::Contradiction { line, .. } => write!(
+                f,
+                "{} cannot satisfy its clues, undoing the last guess",
+                line
+            ),
+        }
+    }
+}
+
+fn state_name(state: Status) -> &'static str {
+    match state {
+        Status::Marked => "marked",
+        Status::Empty => "empty",
+        Status::Unknown => "unknown",
+    }
+}
+
+//writes the positions 1-based, joining consecutive ones into ranges, e.g. "2-4, 7"
+fn write_ranges(f: &mut fmt::Formatter, cells: &[usize]) -> fmt::Result {
+    let mut i = 0;
+    while i < cells.len() {
+        let mut j = i;
+        while j + 1 < cells.len() && cells[j + 1] == cells[j] + 1 {
+            j += 1;
+        }
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        if i == j {
+            write!(f, "{}", cells[i] + 1)?;
+        } else {
+            write!(f, "{}-{}", cells[i] + 1, cells[j] + 1)?;
+        }
+        i = j + 1;
+    }
+    Ok(())
+}
+
+///The log of every cell fixed while solving a puzzle, see [`crate::Puzzle::solve_traced`]
+#[derive(Debug, Default, Clone)]
+pub struct SolveTrace {
+    steps: Vec<Step>,
+    depth: usize,
+    recording: bool,
+}
+
+impl SolveTrace {
+    pub(crate) fn new() -> SolveTrace {
+        SolveTrace {
+            recording: true,
+            ..Default::default()
+        }
+    }
+
+    //A trace that records nothing, for solving without an explanation
+    pub(crate) fn disabled() -> SolveTrace {
+        SolveTrace::default()
+    }
+
+    ///The steps in the order they were taken
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    ///Number of guesses made by the search
+    pub fn guesses(&self) -> usize {
+        self.steps.iter().filter(|step| step.is_guess()).count()
+    }
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub(crate) fn guess(&mut self, row: usize, column: usize, state: Status, depth: usize) {
+        self.depth = depth;
+        if self.recording {
+            self.steps.push(Step::Guess {
+                row,
+                column,
+                state,
+                depth,
+            });
+        }
+    }
+
+    pub(crate) fn probed(
+        &mut self,
+        row: usize,
+        column: usize,
+        forced: Option<Status>,
+        cells: Vec<(usize, usize)>,
+    ) {
+        if self.recording {
+            self.steps.push(Step::Probe {
+                row,
+                column,
+                forced,
+                cells,
+                depth: self.depth,
+            });
+        }
+    }
+
+    pub(crate) fn contradiction(&mut self, line: Line) {
+        if self.recording {
+            self.steps.push(Step::Contradiction {
+                line,
+                depth: self.depth,
+            });
+        }
+    }
+
+    fn deduce(&mut self, line: Line, rule: Rule, state: Status, cells: Vec<usize>) {
+        self.steps.push(Step::Deduction {
+            line,
+            rule,
+            state,
+            cells,
+            depth: self.depth,
+        });
+    }
+
+    //Records the cells a rule changed, grouped by row if rows is true and by column otherwise
+    pub(crate) fn rule_applied(&mut self, rule: Rule, rows: bool, before: &Grid, after: &Grid) {
+        let (height, width) = (before.height(), before.width());
+        let (lines, length) = if rows {
+            (height, width)
+        } else {
+            (width, height)
+        };
+        for n in 0..lines {
+            for state in [Status::Marked, Status::Empty] {
+                let cells: Vec<usize> = (0..length)
+                    .filter(|&p| {
+                        let (i, j) = if rows { (n, p) } else { (p, n) };
+                        before.get(i, j) != state && after.get(i, j) == state
+                    })
+                    .collect();
+                if !cells.is_empty() {
+                    let line = if rows { Line::Row(n) } else { Line::Column(n) };
+                    self.deduce(line, rule, state, cells);
+                }
+            }
+        }
+    }
+
+    //Records the cells the line solver fixed in a line, explaining them by the overlap of a
+    //clue or by being out of reach of every clue where possible
+    pub(crate) fn line_solved(
+        &mut self,
+        line: Line,
+        keys: &[usize],
+        before: &[Status],
+        after: &[Status],
+    ) {
+        let lengths: Vec<usize> = keys.iter().copied().filter(|&k| k > 0).collect();
+        let spans = clue_spans(keys, before).unwrap_or_default();
+        let mut groups: Vec<(Rule, Status, Vec<usize>)> = Vec::new();
+        for p in (0..before.len()).filter(|&p| before[p] != after[p]) {
+            let rule = if after[p] == Status::Marked {
+                spans
+                    .iter()
+                    .zip(lengths.iter())
+                    .find(|&(&(first, last), &len)| last + 1 - len <= p && p < first + len)
+                    .map_or(Rule::LineSolver, |(&(first, last), &clue)| Rule::Overlap {
+                        clue,
+                        first,
+                        last,
+                    })
+            } else if spans.iter().all(|&(first, last)| p < first || p > last) {
+                Rule::Unreachable
+            } else {
+                Rule::LineSolver
+            };
+            match groups
+                .iter_mut()
+                .find(|(r, state, _)| *r == rule && *state == after[p])
+            {
+                Some((_, _, cells)) => cells.push(p),
+                None => groups.push((rule, after[p], vec![p])),
+            }
+        }
+        for (rule, state, cells) in groups {
+            self.deduce(line, rule, state, cells);
+        }
+    }
+}
+
+#[test]
+fn overlap_is_explained() {
+    let mut trace = SolveTrace::new();
+    let before = vec![Status::Unknown; 10];
+    let mut after = before.clone();
+    after[4] = Status::Marked;
+    after[5] = Status::Marked;
+    trace.line_solved(Line::Row(3), &[6], &before, &after);
+    assert_eq!(
+        trace.steps()[0].to_string(),
+        "row 4: overlap of clue 6 in cells 1-10 marks cells 5-6"
+    );
+
+    //a 4 covering the marked cell lies within cells 2-7
+    let cells = |s: &str| -> Vec<Status> {
+        s.chars()
+            .map(|c| match c {
+                '#' => Status::Marked,
+                '.' => Status::Empty,
+                _ => Status::Unknown,
+            })
+            .collect()
+    };
+    trace.line_solved(Line::Column(0), &[4], &cells("????#??"), &cells(".??##??"));
+    assert_eq!(
+        trace.steps()[1].to_string(),
+        "column 1: no clue reaching clears cell 1"
+    );
+    assert_eq!(
+        trace.steps()[2].to_string(),
+        "column 1: overlap of clue 4 in cells 2-7 marks cell 4"
+    );
+}
+
+#[test]
+fn steps_are_described() {
+    let guess = Step::Guess {
+        row: 2,
+        column: 4,
+        state: Status::Marked,
+        depth: 1,
+    };
+    assert_eq!(guess.to_string(), "guess: row 3, column 5 is marked");
+    assert!(guess.is_guess());
+    let deduction = Step::Deduction {
+        line: Line::Column(1),
+        rule: Rule::CompleteGroups,
+        state: Status::Empty,
+        cells: vec![0, 1, 2, 5],
+        depth: 0,
+    };
+    assert_eq!(
+        deduction.to_string(),
+        "column 2: completing a group clears cells 1-3, 6"
+    );
+    let probe = Step::Probe {
+        row: 0,
+        column: 1,
+        forced: Some(Status::Empty),
+        cells: vec![(0, 1)],
+        depth: 0,
+    };
+    assert_eq!(
+        probe.to_string(),
+        "probing row 1, column 2: marking it leads to a contradiction, so it is empty"
+    );
+}
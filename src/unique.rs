@@ -0,0 +1,488 @@
+use crate::board::Status;
+use crate::solver::{Limits, SolveOptions, SolveOutcome};
+use crate::{Puzzle, Solution};
+
+///A cell changed by [`add_givens`] or [`flip_cells`] to make a puzzle uniquely solvable
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub row: usize,
+    pub column: usize,
+    ///The state the cell is given, or flipped to
+    pub marked: bool,
+}
+
+fn state(marked: bool) -> Status {
+    if marked {
+        Status::Marked
+    } else {
+        Status::Empty
+    }
+}
+
+//Cells known after line solving alone, all of them when it solves the puzzle
+fn deduced(puzzle: &Puzzle, limits: &Limits) -> usize {
+    let limits = Limits {
+        max_nodes: Some(0),
+        ..limits.clone()
+    };
+    match puzzle.solve_with_limits(limits) {
+        SolveOutcome::Solved(_) => puzzle.width() * puzzle.height(),
+        SolveOutcome::Timeout(partial) => partial
+            .rows()
+            .iter()
+            .flatten()
+            .filter(|&&state| state != Status::Unknown)
+            .count(),
+        SolveOutcome::Unsat => 0,
+    }
+}
+
+//Up to two solutions of the puzzle, or None if the limits stop the search first
+fn solutions(puzzle: &Puzzle, limits: &Limits) -> Option<Vec<Solution>> {
+    let options = SolveOptions {
+        limits: limits.clone(),
+        ..Default::default()
+    };
+    let (solutions, partial, _) = puzzle.solutions_outcome(&options, 2);
+    match partial {
+        Some(_) => None,
+        None => Some(solutions),
+    }
+}
+
+//Whether the puzzle has a single solution, or None if the limits stop the search first
+fn unique(puzzle: &Puzzle, limits: &Limits) -> Option<bool> {
+    solutions(puzzle, limits).map(|solutions| solutions.len() == 1)
+}
+
+//Cells where another solution of the puzzle disagrees with the picture, none if it is unique,
+//or None if the limits stop the search first
+fn disagreements(
+    puzzle: &Puzzle,
+    picture: &[Vec<bool>],
+    limits: &Limits,
+) -> Option<Vec<(usize, usize)>> {
+    let mut cells = Vec::new();
+    for solution in solutions(puzzle, limits)? {
+        for (i, row) in picture.iter().enumerate() {
+            for (j, &marked) in row.iter().enumerate() {
+                if solution.is_marked(i, j) != marked {
+                    cells.push((i, j));
+                }
+            }
+        }
+    }
+    cells.sort_unstable();
+    cells.dedup();
+    Some(cells)
+}
+
+//The edits of a picture's cells, in row-major order
+fn edits(mut cells: Vec<(usize, usize)>, picture: &[Vec<bool>]) -> Vec<Edit> {
+    cells.sort_unstable();
+    cells
+        .into_iter()
+        .map(|(row, column)| Edit {
+            row,
+            column,
+            marked: picture[row][column],
+        })
+        .collect()
+}
+
+//Largest number of sets of cells tried looking for the fewest edits. Pictures with more sets
+//smaller than the edits found by picking one cell at a time keep those edits.
+const MAX_TRIALS: usize = 10_000;
+
+//Returns true if there are at most MAX_TRIALS sets of fewer than size cells out of cells
+fn searchable(cells: usize, size: usize) -> bool {
+    let (mut total, mut sets) = (0usize, 1usize);
+    for k in 0..size {
+        total = match total.checked_add(sets) {
+            Some(total) if total <= MAX_TRIALS => total,
+            _ => return false,
+        };
+        sets = match sets.checked_mul(cells.saturating_sub(k)) {
+            Some(product) => product / (k + 1),
+            None => return k + 1 == size,
+        };
+    }
+    true
+}
+
+//The first set of cells, by size and then in row-major order, that makes the solution unique,
+//trying every set smaller than best, the smallest set known to work. None if the limits stop
+//one of the checks.
+fn fewest(
+    cells: &[(usize, usize)],
+    best: Vec<(usize, usize)>,
+    mut unique: impl FnMut(&[(usize, usize)]) -> Option<bool>,
+) -> Option<Vec<(usize, usize)>> {
+    for size in 0..best.len() {
+        //indices of the cells of the set tried, in increasing order
+        let mut set: Vec<usize> = (0..size).collect();
+        loop {
+            let tried: Vec<(usize, usize)> = set.iter().map(|&c| cells[c]).collect();
+            if unique(&tried)? {
+                return Some(tried);
+            }
+            //the next set of the same size: the last index that can still move moves by one,
+            //and the ones after it follow it
+            let mut t = size;
+            while t > 0 && set[t - 1] == cells.len() - size + t - 1 {
+                t -= 1;
+            }
+            if t == 0 {
+                break;
+            }
+            set[t - 1] += 1;
+            let moved = set[t - 1];
+            for (k, c) in set[t..].iter_mut().enumerate() {
+                *c = moved + 1 + k;
+            }
+        }
+    }
+    Some(best)
+}
+
+//Every cell of a picture, in row-major order
+fn all_cells(picture: &[Vec<bool>]) -> Vec<(usize, usize)> {
+    (0..picture.len())
+        .flat_map(|i| (0..picture[i].len()).map(move |j| (i, j)))
+        .collect()
+}
+
+///A puzzle made uniquely solvable by [`add_givens`] or [`flip_cells`]
+#[derive(Debug, Clone)]
+pub struct Edited {
+    pub puzzle: Puzzle,
+    ///The cells given or flipped, in row-major order
+    pub edits: Vec<Edit>,
+    ///True if no smaller set of edits makes the solution unique. Pictures with too many sets of
+    ///cells to try keep edits of which none can be left out, though a smaller set may exist.
+    pub minimal: bool,
+}
+
+//The givens picked one at a time: while a second solution exists, the cell where it disagrees
+//with the picture that lets line solving fix the most cells, then without the givens the
+//others make unneeded. On reaching a limit, the givens picked so far.
+fn pick_givens(picture: &[Vec<bool>], limits: &Limits) -> SolveOutcome<Vec<(usize, usize)>> {
+    let mut puzzle = Puzzle::from_picture(picture);
+    let mut givens: Vec<(usize, usize)> = Vec::new();
+    loop {
+        let cells = match disagreements(&puzzle, picture, limits) {
+            Some(cells) => cells,
+            None => return SolveOutcome::Timeout(givens),
+        };
+        let best = cells.into_iter().max_by_key(|&(i, j)| {
+            let mut trial = puzzle.clone();
+            trial.set_given(i, j, state(picture[i][j]));
+            deduced(&trial, limits)
+        });
+        if limits.expired() {
+            return SolveOutcome::Timeout(givens);
+        }
+        match best {
+            Some((i, j)) => {
+                puzzle.set_given(i, j, state(picture[i][j]));
+                givens.push((i, j));
+            }
+            None => break,
+        }
+    }
+    //the first givens are picked with the least help from the others
+    let mut needed = Vec::new();
+    for (n, &(i, j)) in givens.iter().enumerate() {
+        puzzle.set_given(i, j, Status::Unknown);
+        match unique(&puzzle, limits) {
+            Some(true) => {}
+            Some(false) => {
+                puzzle.set_given(i, j, state(picture[i][j]));
+                needed.push((i, j));
+            }
+            None => {
+                needed.extend_from_slice(&givens[n..]);
+                return SolveOutcome::Timeout(needed);
+            }
+        }
+    }
+    SolveOutcome::Solved(needed)
+}
+
+///Picks the fewest cells of a picture (rows of the same length, true for marked cells) to give
+///before solving, so that the puzzle of the picture has it as its only solution. Givens are
+///first picked one at a time: while a second solution exists, the cell where it disagrees with
+///the picture that lets line solving fix the most cells is given, and once the solution is
+///unique, the givens the others make unneeded are dropped. Every smaller set of cells is then
+///tried in turn, on pictures small enough for that, see [`Edited::minimal`].
+///
+///Every search stops at the limits. Returns the puzzle with the givens set, or on reaching a
+///limit before any set of givens is found [`SolveOutcome::Timeout`] with the givens picked so
+///far, which may not make the solution unique yet.
+pub fn add_givens(picture: &[Vec<bool>], limits: &Limits) -> SolveOutcome<Edited> {
+    let given = |cells: &[(usize, usize)]| {
+        let mut puzzle = Puzzle::from_picture(picture);
+        for &(i, j) in cells {
+            puzzle.set_given(i, j, state(picture[i][j]));
+        }
+        puzzle
+    };
+    let edited = |cells: Vec<(usize, usize)>, minimal| Edited {
+        puzzle: given(&cells),
+        edits: edits(cells, picture),
+        minimal,
+    };
+    let picked = match pick_givens(picture, limits) {
+        SolveOutcome::Solved(picked) => picked,
+        SolveOutcome::Timeout(picked) => return SolveOutcome::Timeout(edited(picked, false)),
+        SolveOutcome::Unsat => return SolveOutcome::Unsat,
+    };
+    let cells = all_cells(picture);
+    if !searchable(cells.len(), picked.len()) {
+        return SolveOutcome::Solved(edited(picked, false));
+    }
+    match fewest(&cells, picked.clone(), |set| unique(&given(set), limits)) {
+        Some(givens) => SolveOutcome::Solved(edited(givens, true)),
+        None => SolveOutcome::Solved(edited(picked, false)),
+    }
+}
+
+//The flips picked one at a time as in pick_givens, each cell flipped at most once. Unsat if
+//no more cells can be flipped and the solution is still not unique, and on reaching a limit,
+//the flips made so far.
+fn pick_flips(original: &[Vec<bool>], limits: &Limits) -> SolveOutcome<Vec<(usize, usize)>> {
+    let mut picture = original.to_vec();
+    let mut flipped: Vec<(usize, usize)> = Vec::new();
+    loop {
+        let cells = match disagreements(&Puzzle::from_picture(&picture), &picture, limits) {
+            Some(cells) => cells,
+            None => return SolveOutcome::Timeout(flipped),
+        };
+        if cells.is_empty() {
+            break;
+        }
+        let mut best = None;
+        for (i, j) in cells.into_iter().filter(|cell| !flipped.contains(cell)) {
+            picture[i][j] = !picture[i][j];
+            let known = deduced(&Puzzle::from_picture(&picture), limits);
+            picture[i][j] = !picture[i][j];
+            if best.is_none_or(|(most, _)| known >= most) {
+                best = Some((known, (i, j)));
+            }
+        }
+        if limits.expired() {
+            return SolveOutcome::Timeout(flipped);
+        }
+        let (i, j) = match best {
+            Some((_, cell)) => cell,
+            None => return SolveOutcome::Unsat,
+        };
+        picture[i][j] = !picture[i][j];
+        flipped.push((i, j));
+    }
+    for &(i, j) in flipped.iter() {
+        picture[i][j] = !picture[i][j];
+        match unique(&Puzzle::from_picture(&picture), limits) {
+            Some(true) => {}
+            Some(false) => picture[i][j] = !picture[i][j],
+            None => {
+                picture[i][j] = !picture[i][j];
+                flipped.retain(|&(i, j)| picture[i][j] != original[i][j]);
+                return SolveOutcome::Timeout(flipped);
+            }
+        }
+    }
+    flipped.retain(|&(i, j)| picture[i][j] != original[i][j]);
+    SolveOutcome::Solved(flipped)
+}
+
+///Flips the fewest cells of a picture (rows of the same length, true for marked cells) so that
+///its puzzle has a single solution, for pictures that may change a little but should be solved
+///from the clues alone. Flips are first picked one at a time as givens are in [`add_givens`],
+///each cell flipped at most once, and flips the others make unneeded are undone. Every smaller
+///set of cells is then tried in turn, on pictures small enough for that, see
+///[`Edited::minimal`].
+///
+///Every search stops at the limits. Returns the puzzle of the changed picture,
+///[`SolveOutcome::Unsat`] if no more cells can be flipped and the solution is still not
+///unique, or on reaching a limit before any set of flips is found [`SolveOutcome::Timeout`]
+///with the flips made so far.
+pub fn flip_cells(original: &[Vec<bool>], limits: &Limits) -> SolveOutcome<Edited> {
+    let flip = |cells: &[(usize, usize)]| {
+        let mut picture = original.to_vec();
+        for &(i, j) in cells {
+            picture[i][j] = !picture[i][j];
+        }
+        picture
+    };
+    let edited = |cells: Vec<(usize, usize)>, minimal| {
+        let picture = flip(&cells);
+        Edited {
+            puzzle: Puzzle::from_picture(&picture),
+            edits: edits(cells, &picture),
+            minimal,
+        }
+    };
+    let picked = match pick_flips(original, limits) {
+        SolveOutcome::Solved(picked) => picked,
+        SolveOutcome::Timeout(picked) => return SolveOutcome::Timeout(edited(picked, false)),
+        SolveOutcome::Unsat => return SolveOutcome::Unsat,
+    };
+    let cells = all_cells(original);
+    if !searchable(cells.len(), picked.len()) {
+        return SolveOutcome::Solved(edited(picked, false));
+    }
+    let fewest = fewest(&cells, picked.clone(), |set| {
+        unique(&Puzzle::from_picture(&flip(set)), limits)
+    });
+    match fewest {
+        Some(flips) => SolveOutcome::Solved(edited(flips, true)),
+        None => SolveOutcome::Solved(edited(picked, false)),
+    }
+}
+
+#[cfg(test)]
+fn picture(rows: &[&str]) -> Vec<Vec<bool>> {
+    rows.iter()
+        .map(|row| row.chars().map(|c| c == '#').collect())
+        .collect()
+}
+
+#[test]
+fn givens_make_a_picture_unique() {
+    let fixed = |picture: &[Vec<bool>]| match add_givens(picture, &Limits::default()) {
+        SolveOutcome::Solved(fixed) => fixed,
+        _ => panic!("no givens found"),
+    };
+    let diagonal = picture(&["#.", ".#"]);
+    let fixed_diagonal = fixed(&diagonal);
+    assert_eq!(fixed_diagonal.edits.len(), 1);
+    assert!(fixed_diagonal.minimal);
+    assert!(fixed_diagonal.puzzle.is_unique());
+    assert_eq!(
+        fixed_diagonal.puzzle.solve().unwrap().to_string(),
+        "#.\n.#\n"
+    );
+
+    //every row can take the cell of any of four columns
+    let scattered = picture(&["#.....", ".#....", "......", "....#.", ".....#"]);
+    let Edited {
+        mut puzzle,
+        edits: givens,
+        minimal,
+    } = fixed(&scattered);
+    assert_eq!(givens.len(), 3);
+    assert!(minimal);
+    assert!(puzzle.is_unique());
+    for given in givens {
+        assert!(scattered[given.row][given.column] == given.marked);
+        puzzle.set_given(given.row, given.column, Status::Unknown);
+        assert!(!puzzle.is_unique());
+        puzzle.set_given(given.row, given.column, state(given.marked));
+    }
+    let unique = picture(&["##", "#."]);
+    assert!(fixed(&unique).edits.is_empty());
+
+    //one given does where picking them one at a time takes two
+    let greedy = picture(&["#..#..", "......", "......", "....##", ".#..##", "#.#..."]);
+    match pick_givens(&greedy, &Limits::default()) {
+        SolveOutcome::Solved(picked) => assert_eq!(picked.len(), 2),
+        _ => panic!("nothing picked"),
+    }
+    let fewest = fixed(&greedy);
+    assert_eq!(fewest.edits.len(), 1);
+    assert!(fewest.minimal && fewest.puzzle.is_unique());
+
+    //a search stopped by the limits leaves the givens picked so far
+    let cancelled = Limits {
+        cancel: Some(std::sync::Arc::new(std::sync::atomic::AtomicBool::new(
+            true,
+        ))),
+        ..Default::default()
+    };
+    match add_givens(&scattered, &cancelled) {
+        SolveOutcome::Timeout(edited) => {
+            assert!(edited.edits.is_empty() && edited.puzzle.givens().is_empty())
+        }
+        _ => panic!("the limits were not reached"),
+    }
+}
+
+#[test]
+fn fewest_sets_are_searched() {
+    assert!(searchable(100, 3));
+    assert!(!searchable(1000, 3));
+    assert!(searchable(usize::MAX, 1));
+    assert!(!searchable(usize::MAX, 3));
+    //the first working set of the smallest size, in order
+    let cells: Vec<(usize, usize)> = (0..5).map(|j| (0, j)).collect();
+    let mut tried = Vec::new();
+    let found = fewest(&cells, cells.clone(), |set| {
+        tried.push(set.to_vec());
+        Some(set.contains(&(0, 2)) && set.contains(&(0, 4)))
+    });
+    assert_eq!(found, Some(vec![(0, 2), (0, 4)]));
+    assert_eq!(tried.len(), 1 + 5 + 9);
+    //none smaller than best works, or the limits stop a check
+    assert_eq!(
+        fewest(&cells, vec![(0, 1)], |_| Some(false)),
+        Some(vec![(0, 1)])
+    );
+    assert_eq!(fewest(&cells, vec![(0, 1)], |_| None), None);
+}
+
+#[test]
+fn flips_make_a_picture_unique() {
+    let scattered = picture(&["#.....", ".#....", "......", "....#.", ".....#"]);
+    let Edited {
+        puzzle,
+        edits: flips,
+        minimal,
+    } = match flip_cells(&scattered, &Limits::default()) {
+        SolveOutcome::Solved(fixed) => fixed,
+        _ => panic!("no flips found"),
+    };
+    assert!(!flips.is_empty() && minimal);
+    assert!(puzzle.is_unique());
+    assert!(puzzle.givens().is_empty());
+    let solution = puzzle.solve().unwrap();
+    for (i, row) in scattered.iter().enumerate() {
+        for (j, &marked) in row.iter().enumerate() {
+            let flipped = flips.iter().any(|f| (f.row, f.column) == (i, j));
+            assert_eq!(solution.is_marked(i, j), marked != flipped);
+        }
+    }
+    //one flip does where picking them one at a time takes two
+    let greedy = picture(&["#...", "...#", "....", "...#", "#..."]);
+    match pick_flips(&greedy, &Limits::default()) {
+        SolveOutcome::Solved(picked) => assert_eq!(picked.len(), 2),
+        _ => panic!("nothing picked"),
+    }
+    match flip_cells(&greedy, &Limits::default()) {
+        SolveOutcome::Solved(fewest) => {
+            assert_eq!(fewest.edits.len(), 1);
+            assert!(fewest.minimal && fewest.puzzle.is_unique());
+        }
+        _ => panic!("no flips found"),
+    }
+    //undoing any flip brings back a second solution
+    let mut picture: Vec<Vec<bool>> = solution
+        .rows()
+        .iter()
+        .map(|row| row.iter().map(|&state| state == Status::Marked).collect())
+        .collect();
+    for flip in flips {
+        picture[flip.row][flip.column] = !flip.marked;
+        assert!(!Puzzle::from_picture(&picture).is_unique());
+        picture[flip.row][flip.column] = flip.marked;
+    }
+
+    let limits = Limits {
+        max_nodes: Some(0),
+        ..Default::default()
+    };
+    assert!(matches!(
+        flip_cells(&scattered, &limits),
+        SolveOutcome::Timeout(_)
+    ));
+}
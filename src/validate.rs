@@ -0,0 +1,211 @@
+use crate::Puzzle;
+use std::error::Error;
+use std::fmt;
+
+///A row or column of the grid, indexed from 0
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Line::Row(i) => write!(f, "row {}", i + 1),
+            Line::Column(i) => write!(f, "column {}", i + 1),
+        }
+    }
+}
+
+///A reason the clues of a puzzle cannot describe any grid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    ///The puzzle has no rows or no columns
+    EmptyGrid { width: usize, height: usize },
+    ///The clues of a line need more cells than the line has
+    ClueTooLong {
+        line: Line,
+        needed: usize,
+        length: usize,
+    },
+    ///A 0 clue appears next to other clues
+    MisplacedZero { line: Line },
+    ///The row clues mark a different number of cells than the column clues
+    TotalMismatch { rows: usize, columns: usize },
+    ///A clue of a colored puzzle refers to a color missing from the palette
+    UnknownColor { line: Line, color: usize },
+    ///The row clues paint a different number of cells in the named color than the column clues
+    ColorMismatch {
+        color: String,
+        rows: usize,
+        columns: usize,
+    },
+    ///The palette has more colors than the solver supports
+    TooManyColors { colors: usize, max: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::EmptyGrid { width, height } => {
+                write!(f, "the grid is {}x{}, it has no cells", width, height)
+            }
+            ValidationError::ClueTooLong {
+                line,
+                needed,
+                length,
+            } => write!(
+                f,
+                "{}: clues need {} cells but the line only has {}",
+                line, needed, length
+            ),
+            ValidationError::MisplacedZero { line } => {
+                write!(f, "{}: a 0 clue must be the only clue of its line", line)
+            }
+            ValidationError::TotalMismatch { rows, columns } => write!(
+                f,
+                "row clues mark {} cells but column clues mark {}",
+                rows, columns
+            ),
+            ValidationError::UnknownColor { line, color } => {
+                write!(
+                    f,
+                    "{}: clue of color {} missing from the palette",
+                    line,
+                    color + 1
+                )
+            }
+            ValidationError::ColorMismatch {
+                color,
+                rows,
+                columns,
+            } => write!(
+                f,
+                "row clues paint {} cells {} but column clues paint {}",
+                rows, color, columns
+            ),
+            ValidationError::TooManyColors { colors, max } => write!(
+                f,
+                "the palette has {} colors, at most {} are supported",
+                colors, max
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+//Checks every row and column of the puzzle, collecting all the problems found
+pub(crate) fn validate(puzzle: &Puzzle) -> Result<(), Vec<ValidationError>> {
+    let (width, height) = (puzzle.width(), puzzle.height());
+    if width == 0 || height == 0 {
+        return Err(vec![ValidationError::EmptyGrid { width, height }]);
+    }
+
+    let rows = puzzle.row_clues();
+    let columns = puzzle.column_clues();
+    let mut errors = Vec::new();
+    let lines = rows
+        .iter()
+        .enumerate()
+        .map(|(i, clues)| (Line::Row(i), clues, width))
+        .chain(
+            columns
+                .iter()
+                .enumerate()
+                .map(|(i, clues)| (Line::Column(i), clues, height)),
+        );
+    for (line, clues, length) in lines {
+        if clues.len() > 1 && clues.contains(&0) {
+            errors.push(ValidationError::MisplacedZero { line });
+            continue;
+        }
+        let needed = clues
+            .iter()
+            .try_fold(clues.len().saturating_sub(1), |sum, &n| sum.checked_add(n));
+        //clues too long to count are reported as needing usize::MAX cells
+        let needed = needed.unwrap_or(usize::MAX);
+        if needed > length {
+            errors.push(ValidationError::ClueTooLong {
+                line,
+                needed,
+                length,
+            });
+        }
+    }
+
+    //a total that overflows comes from a line already reported as too long
+    let total = |clues: &[Vec<usize>]| {
+        clues
+            .iter()
+            .flatten()
+            .try_fold(0usize, |sum, &n| sum.checked_add(n))
+    };
+    if let (Some(rows), Some(columns)) = (total(&rows), total(&columns)) {
+        if rows != columns {
+            errors.push(ValidationError::TotalMismatch { rows, columns });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[test]
+fn valid_puzzle() {
+    let p = Puzzle::new(vec![vec![1], vec![0]], vec![vec![1], vec![]]);
+    assert_eq!(validate(&p), Ok(()));
+}
+
+#[test]
+fn reports_every_problem() {
+    let p = Puzzle::new(
+        vec![vec![2, 1], vec![0], vec![1, 0]],
+        vec![vec![1], vec![3]],
+    );
+    assert_eq!(
+        validate(&p),
+        Err(vec![
+            ValidationError::ClueTooLong {
+                line: Line::Row(0),
+                needed: 4,
+                length: 2
+            },
+            ValidationError::MisplacedZero { line: Line::Row(2) },
+        ])
+    );
+    let p = Puzzle::new(vec![vec![usize::MAX, 1]], vec![vec![1]]);
+    assert_eq!(
+        validate(&p),
+        Err(vec![ValidationError::ClueTooLong {
+            line: Line::Row(0),
+            needed: usize::MAX,
+            length: 1
+        }])
+    );
+}
+
+#[test]
+fn totals_must_match() {
+    let p = Puzzle::new(vec![vec![1], vec![1]], vec![vec![2], vec![1]]);
+    assert_eq!(
+        validate(&p),
+        Err(vec![ValidationError::TotalMismatch {
+            rows: 2,
+            columns: 3
+        }])
+    );
+}
+
+#[test]
+fn empty_grid() {
+    let p = Puzzle::new(vec![], vec![vec![1]]);
+    assert!(matches!(
+        validate(&p),
+        Err(ref e) if e[0] == ValidationError::EmptyGrid { width: 1, height: 0 }
+    ));
+}
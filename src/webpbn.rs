@@ -0,0 +1,446 @@
+use crate::color::{needed_cells, Color, ColorClue, ColorPuzzle, ColorSolution};
+use crate::parse::ParseError;
+use crate::Puzzle;
+use roxmltree::{Document, Node, ParsingOptions};
+use std::fs;
+use std::path::Path;
+
+///A puzzle read from a webpbn.com XML export, see [`parse_webpbn`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebpbnPuzzle {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    ///The clues, with every color of the file except the background in the palette, the
+    ///default color first
+    pub puzzle: ColorPuzzle,
+    ///The intended picture, if the file has a goal `<solution>`
+    pub goal: Option<ColorSolution>,
+}
+
+impl WebpbnPuzzle {
+    ///The clues as a black and white [`Puzzle`], None if they use more than one color
+    pub fn to_puzzle(&self) -> Option<Puzzle> {
+        let (rows, columns) = (self.puzzle.row_clues(), self.puzzle.column_clues());
+        let mut colors = rows.iter().chain(columns.iter()).flatten().map(|c| c.color);
+        let first = colors.next();
+        if !colors.all(|color| Some(color) == first) {
+            return None;
+        }
+        let lengths = |lines: &[Vec<ColorClue>]| -> Vec<Vec<usize>> {
+            lines
+                .iter()
+                .map(|clues| clues.iter().map(|c| c.length).collect())
+                .collect()
+        };
+        Some(Puzzle::new(lengths(rows), lengths(columns)))
+    }
+}
+
+///Returns true if the text looks like XML rather than one of the plain text formats
+pub fn is_webpbn(text: &str) -> bool {
+    text.trim_start().starts_with('<')
+}
+
+///Reads the webpbn XML file at the given path, see [`parse_webpbn`]
+pub fn read_webpbn<P: AsRef<Path>>(file: P) -> Result<WebpbnPuzzle, ParseError> {
+    parse_webpbn(&fs::read_to_string(file)?)
+}
+
+///Parses the first `<puzzle>` of a webpbn XML export.
+///
+///Colors are declared by `<color name="red" char="r">f00</color>` elements, white and black
+///being known without one. The `backgroundcolor` and `defaultcolor` attributes of the puzzle
+///(white and black unless given) pick the color of empty cells and of counts without a
+///`color` attribute. Each `<clues type="columns">` and `<clues type="rows">` element holds
+///one `<line>` per column or row, with a `<count>` per block. The picture of a
+///`<solution type="goal">` is read as rows of color chars between `|` characters.
+pub fn parse_webpbn(text: &str) -> Result<WebpbnPuzzle, ParseError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    let doc = Document::parse_with_options(text, options).map_err(|e| {
+        let pos = e.pos();
+        //the position is already reported separately
+        let message = e.to_string();
+        let suffix = format!(" at {}:{}", pos.row, pos.col);
+        ParseError::Xml {
+            line: pos.row as usize,
+            column: pos.col as usize,
+            message: message
+                .strip_suffix(&suffix)
+                .unwrap_or(&message)
+                .to_string(),
+        }
+    })?;
+    let error = |node: Node, message: String| {
+        let pos = doc.text_pos_at(node.range().start);
+        ParseError::Xml {
+            line: pos.row as usize,
+            column: pos.col as usize,
+            message,
+        }
+    };
+    let puzzle = doc
+        .descendants()
+        .find(|node| node.has_tag_name("puzzle"))
+        .ok_or_else(|| error(doc.root_element(), "no <puzzle> element".to_string()))?;
+    match puzzle.attribute("type") {
+        None | Some("grid") => {}
+        Some(kind) => return Err(error(puzzle, format!("unsupported puzzle type '{}'", kind))),
+    }
+
+    let background = puzzle.attribute("backgroundcolor").unwrap_or("white");
+    let default = puzzle.attribute("defaultcolor").unwrap_or("black");
+    let colors = parse_colors(&doc, puzzle, &[background, default])?;
+    let known = |name: &str| colors.iter().any(|color| color.name == name);
+    if !known(background) || !known(default) || background == default {
+        return Err(error(
+            puzzle,
+            format!(
+                "bad background color '{}' or default color '{}'",
+                background, default
+            ),
+        ));
+    }
+    //the default color first, so a clue without a color has the first one as in the text format
+    let palette: Vec<Color> = colors
+        .iter()
+        .filter(|color| color.name == default)
+        .chain(
+            colors
+                .iter()
+                .filter(|color| color.name != default && color.name != background),
+        )
+        .cloned()
+        .collect();
+    let background_symbol = colors
+        .iter()
+        .find(|color| color.name == background)
+        .map(|color| color.symbol);
+
+    let mut columns = None;
+    let mut rows = None;
+    for clues in children(puzzle, "clues") {
+        let lines = match clues.attribute("type") {
+            Some("columns") => &mut columns,
+            Some("rows") => &mut rows,
+            _ => {
+                return Err(error(
+                    clues,
+                    "<clues> needs a type of columns or rows".to_string(),
+                ))
+            }
+        };
+        let mut parsed = Vec::new();
+        for line in children(clues, "line") {
+            parsed.push((line, parse_line(&doc, line, default, &palette)?));
+        }
+        *lines = Some(parsed);
+    }
+    let missing = |kind: &str| error(puzzle, format!("no <clues type=\"{}\"> element", kind));
+    let columns = columns.ok_or_else(|| missing("columns"))?;
+    let rows = rows.ok_or_else(|| missing("rows"))?;
+    if columns.is_empty() || rows.is_empty() {
+        return Err(error(puzzle, "the grid has no cells".to_string()));
+    }
+    let fits = |lines: Vec<(Node, Vec<ColorClue>)>, length: usize| {
+        lines
+            .into_iter()
+            .map(|(line, clues)| {
+                if needed_cells(&clues) > length {
+                    let pos = doc.text_pos_at(line.range().start);
+                    return Err(ParseError::ClueTooLong {
+                        line: pos.row as usize,
+                        column: pos.col as usize,
+                        token: clues
+                            .iter()
+                            .map(|c| c.length.to_string())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                        length,
+                    });
+                }
+                Ok(clues)
+            })
+            .collect::<Result<Vec<_>, ParseError>>()
+    };
+    let (width, height) = (columns.len(), rows.len());
+    let columns = fits(columns, height)?;
+    let rows = fits(rows, width)?;
+
+    let goal = match children(puzzle, "solution")
+        .find(|solution| solution.attribute("type").unwrap_or("goal") == "goal")
+    {
+        Some(solution) => {
+            let image = children(solution, "image")
+                .next()
+                .ok_or_else(|| error(solution, "<solution> without an <image>".to_string()))?;
+            let cells = parse_image(image, &palette, background_symbol, width, height)
+                .map_err(|message| error(image, message))?;
+            Some(ColorSolution::from_rows(palette.clone(), cells))
+        }
+        None => None,
+    };
+
+    let text_of = |name: &str| {
+        children(puzzle, name)
+            .next()
+            .and_then(|node| node.text())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(String::from)
+    };
+    Ok(WebpbnPuzzle {
+        title: text_of("title"),
+        author: text_of("author"),
+        puzzle: ColorPuzzle::new(palette, rows, columns),
+        goal,
+    })
+}
+
+//The child elements of a node with the given name
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| child.has_tag_name(name))
+}
+
+//Reads the <color> elements of a puzzle, adding white and black if the puzzle uses them
+//without declaring them. A color without a char gets the first letter of its name, or
+//another letter if that one is taken.
+fn parse_colors(doc: &Document, puzzle: Node, used: &[&str]) -> Result<Vec<Color>, ParseError> {
+    let mut declared: Vec<(String, Option<char>, [u8; 3])> = Vec::new();
+    for node in children(puzzle, "color") {
+        let pos = doc.text_pos_at(node.range().start);
+        let value = node.text().unwrap_or("").trim();
+        let bad = |token: &str| ParseError::BadPalette {
+            line: pos.row as usize,
+            column: pos.col as usize,
+            token: token.to_string(),
+        };
+        let name = node.attribute("name").ok_or_else(|| bad(value))?;
+        let mut chars = node.attribute("char").map(|c| c.chars());
+        let symbol = match chars.as_mut().map(|c| (c.next(), c.next())) {
+            None => None,
+            Some((Some(c), None)) if !"|[]".contains(c) => Some(c),
+            Some(_) => return Err(bad(node.attribute("char").unwrap_or(""))),
+        };
+        let rgb = parse_rgb(value).ok_or_else(|| bad(value))?;
+        let repeated = declared.iter().any(|(other, other_symbol, _)| {
+            other == name || (symbol.is_some() && *other_symbol == symbol)
+        });
+        if repeated {
+            return Err(bad(name));
+        }
+        declared.push((name.to_string(), symbol, rgb));
+    }
+    for (name, symbol, rgb) in [("white", '.', [255, 255, 255]), ("black", '#', [0, 0, 0])] {
+        if used.contains(&name) && declared.iter().all(|(other, _, _)| other != name) {
+            let taken = declared.iter().any(|(_, other, _)| *other == Some(symbol));
+            declared.push((name.to_string(), Some(symbol).filter(|_| !taken), rgb));
+        }
+    }
+    let mut colors: Vec<Color> = Vec::with_capacity(declared.len());
+    for (name, symbol, rgb) in declared.iter() {
+        let taken = |c: char| {
+            colors.iter().any(|color| color.symbol == c)
+                || declared.iter().any(|(_, other, _)| *other == Some(c))
+        };
+        let symbol = symbol.unwrap_or_else(|| {
+            name.chars()
+                .take(1)
+                .chain('a'..='z')
+                .chain('A'..='Z')
+                .find(|&c| !taken(c))
+                .unwrap_or('?')
+        });
+        colors.push(Color {
+            name: name.clone(),
+            symbol,
+            rgb: *rgb,
+        });
+    }
+    Ok(colors)
+}
+
+//Parses a color value of 3 or 6 hex digits, with an optional '#'
+fn parse_rgb(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !hex.is_ascii() {
+        return None;
+    }
+    let digits = match hex.len() {
+        3 => 1,
+        6 => 2,
+        _ => return None,
+    };
+    let mut rgb = [0; 3];
+    for (i, component) in rgb.iter_mut().enumerate() {
+        let part = &hex[digits * i..digits * (i + 1)];
+        let n = u8::from_str_radix(part, 16).ok()?;
+        *component = if digits == 1 { n * 17 } else { n };
+    }
+    Some(rgb)
+}
+
+//Parses the <count> elements of a <line>
+fn parse_line(
+    doc: &Document,
+    line: Node,
+    default: &str,
+    palette: &[Color],
+) -> Result<Vec<ColorClue>, ParseError> {
+    let mut clues = Vec::new();
+    for count in children(line, "count") {
+        let pos = doc.text_pos_at(count.range().start);
+        let token = count.text().unwrap_or("").trim();
+        let length = token.parse().map_err(|_| ParseError::NonNumericClue {
+            line: pos.row as usize,
+            column: pos.col as usize,
+            token: token.to_string(),
+        })?;
+        let name = count.attribute("color").unwrap_or(default);
+        let color = palette
+            .iter()
+            .position(|color| color.name == name)
+            .ok_or_else(|| ParseError::UnknownColor {
+                line: pos.row as usize,
+                column: pos.col as usize,
+                token: name.to_string(),
+            })?;
+        if length > 0 {
+            clues.push(ColorClue { length, color });
+        }
+    }
+    Ok(clues)
+}
+
+//Reads the rows of a solution image, one color char per cell between '|' characters
+fn parse_image(
+    image: Node,
+    palette: &[Color],
+    background: Option<char>,
+    width: usize,
+    height: usize,
+) -> Result<Vec<Vec<Option<usize>>>, String> {
+    let mut rows = Vec::new();
+    for text in image.text().unwrap_or("").lines() {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let text = text.strip_prefix('|').unwrap_or(text);
+        let text = text.strip_suffix('|').unwrap_or(text);
+        let mut row = Vec::with_capacity(width);
+        for c in text.chars() {
+            if Some(c) == background {
+                row.push(None);
+            } else if let Some(color) = palette.iter().position(|color| color.symbol == c) {
+                row.push(Some(color));
+            } else if c == '[' {
+                return Err("cells with a choice of colors are not supported".to_string());
+            } else {
+                return Err(format!("unknown color char '{}' in the image", c));
+            }
+        }
+        if row.len() != width {
+            return Err(format!(
+                "image row {} has {} cells instead of {}",
+                rows.len() + 1,
+                row.len(),
+                width
+            ));
+        }
+        rows.push(row);
+    }
+    if rows.len() != height {
+        return Err(format!(
+            "image has {} rows instead of {}",
+            rows.len(),
+            height
+        ));
+    }
+    Ok(rows)
+}
+
+#[test]
+fn parse_black_and_white_puzzle() {
+    let text = r#"<?xml version="1.0"?>
+<!DOCTYPE pbn SYSTEM "https://webpbn.com/pbn-0.3.dtd">
+<puzzleset>
+<puzzle type="grid" defaultcolor="black">
+<title>Corner</title>
+<author>Someone</author>
+<color name="white" char=".">fff</color>
+<color name="black" char="X">000</color>
+<clues type="columns">
+<line><count>2</count></line>
+<line><count>1</count></line>
+</clues>
+<clues type="rows">
+<line><count>2</count></line>
+<line><count>1</count></line>
+</clues>
+<solution type="goal"><image>
+|XX|
+|X.|
+</image></solution>
+</puzzle>
+</puzzleset>
+"#;
+    assert!(is_webpbn(text));
+    let p = parse_webpbn(text).unwrap();
+    assert_eq!(p.title.as_deref(), Some("Corner"));
+    assert_eq!(p.author.as_deref(), Some("Someone"));
+    assert_eq!(p.puzzle.palette()[0].symbol, 'X');
+    let goal = p.goal.clone().unwrap();
+    assert_eq!(goal.to_string(), "XX\nX.\n");
+    let puzzle = p.to_puzzle().unwrap();
+    assert_eq!(puzzle.row_clues(), vec![vec![2], vec![1]]);
+    assert_eq!(puzzle.solve().unwrap().to_string(), "##\n#.\n");
+    assert_eq!(p.puzzle.solve(), Some(goal));
+}
+
+#[test]
+fn read_colored_puzzle() {
+    let p = read_webpbn("./src/puzzles/webpbn1.xml").unwrap();
+    assert_eq!(p.title.as_deref(), Some("Cherries"));
+    assert_eq!(p.puzzle.palette().len(), 3);
+    assert!(p.to_puzzle().is_none());
+    assert!(p.puzzle.is_unique());
+    assert_eq!(p.puzzle.solve(), p.goal);
+}
+
+#[test]
+fn bad_webpbn_files() {
+    let e = parse_webpbn("<puzzleset><puzzle>\n</puzzleset>").unwrap_err();
+    assert!(matches!(e, ParseError::Xml { line: 2, .. }));
+    let e = parse_webpbn("<puzzle><clues type=\"rows\"><line/></clues></puzzle>").unwrap_err();
+    assert_eq!(e.to_string(), "no <clues type=\"columns\"> element");
+    let text = "<puzzle>\n<clues type=\"columns\"><line><count>x</count></line></clues>\n\
+                <clues type=\"rows\"><line><count>1</count></line></clues></puzzle>";
+    let e = parse_webpbn(text).unwrap_err();
+    assert!(matches!(
+        e,
+        ParseError::NonNumericClue {
+            line: 2,
+            column: 29,
+            ..
+        }
+    ));
+    let text = "<puzzle><clues type=\"columns\"><line><count>1</count></line></clues>\
+                <clues type=\"rows\"><line><count color=\"red\">1</count></line></clues></puzzle>";
+    assert!(matches!(
+        parse_webpbn(text).unwrap_err(),
+        ParseError::UnknownColor { .. }
+    ));
+    let text = "<puzzle><clues type=\"columns\"><line><count>2</count></line></clues>\
+                <clues type=\"rows\"><line><count>1</count></line></clues></puzzle>";
+    assert!(matches!(
+        parse_webpbn(text).unwrap_err(),
+        ParseError::ClueTooLong { length: 1, .. }
+    ));
+}